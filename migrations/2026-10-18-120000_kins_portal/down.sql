@@ -0,0 +1 @@
+alter table kins drop column portal;
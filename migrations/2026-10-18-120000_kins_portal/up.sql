@@ -0,0 +1,9 @@
+alter table kins add column portal boolean not null default false;
+
+-- galactic activation portals
+update kins set portal = true where num in (
+    1, 20, 22, 39, 43, 50, 51, 58, 64, 69, 72, 77, 85, 88, 93, 96,
+    106, 107, 108, 109, 110, 111, 112, 113, 114, 115,
+    146, 147, 148, 149, 150, 151, 152, 153, 154, 155,
+    165, 168, 173, 176, 184, 189, 192, 197, 203, 210, 211, 218, 222, 239, 241, 260
+);
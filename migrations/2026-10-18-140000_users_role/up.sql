@@ -0,0 +1,2 @@
+alter table users add column role varchar not null default 'member'
+    check (role in ('admin', 'editor', 'member'));
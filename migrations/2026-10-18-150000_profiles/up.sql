@@ -0,0 +1,10 @@
+create table if not exists profiles (
+    id serial primary key,
+    user_id int not null references users (id) on delete cascade,
+    name varchar not null,
+    birthday date not null,
+    notes text not null default '',
+    created_at timestamp not null default now()
+);
+
+create index profiles_user_id on profiles (user_id);
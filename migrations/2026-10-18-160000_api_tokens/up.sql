@@ -0,0 +1,10 @@
+create table if not exists api_tokens (
+    id serial primary key,
+    user_id int not null references users (id) on delete cascade,
+    name varchar not null,
+    -- sha-256 of the token, the token itself is shown once
+    token_hash varchar not null unique,
+    created_at timestamp not null default now()
+);
+
+create index api_tokens_user_id on api_tokens (user_id);
@@ -0,0 +1,15 @@
+alter table kins drop constraint if exists kins_num_check;
+alter table kins drop constraint if exists kins_num_key;
+
+alter table tones drop constraint if exists tones_num_check;
+alter table tones drop constraint if exists tones_num_key;
+
+alter table glyphs drop constraint if exists glyphs_num_check;
+alter table glyphs drop constraint if exists glyphs_num_key;
+
+insert into glyphs select * from glyphs_num_conflicts;
+drop table glyphs_num_conflicts;
+insert into tones select * from tones_num_conflicts;
+drop table tones_num_conflicts;
+insert into kins select * from kins_num_conflicts;
+drop table kins_num_conflicts;
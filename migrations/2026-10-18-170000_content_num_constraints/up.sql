@@ -0,0 +1,29 @@
+-- rows that would break the constraints are moved to <table>_num_conflicts
+-- instead of failing the deploy: numbers out of range and duplicates,
+-- of which the row with the lowest id stays
+create table glyphs_num_conflicts as
+    select * from glyphs t
+    where t.num not between 1 and 20
+        or exists (select 1 from glyphs o where o.num = t.num and o.id < t.id);
+delete from glyphs where id in (select id from glyphs_num_conflicts);
+
+create table tones_num_conflicts as
+    select * from tones t
+    where t.num not between 1 and 13
+        or exists (select 1 from tones o where o.num = t.num and o.id < t.id);
+delete from tones where id in (select id from tones_num_conflicts);
+
+create table kins_num_conflicts as
+    select * from kins t
+    where t.num not between 1 and 260
+        or exists (select 1 from kins o where o.num = t.num and o.id < t.id);
+delete from kins where id in (select id from kins_num_conflicts);
+
+alter table glyphs add constraint glyphs_num_key unique (num);
+alter table glyphs add constraint glyphs_num_check check (num between 1 and 20);
+
+alter table tones add constraint tones_num_key unique (num);
+alter table tones add constraint tones_num_check check (num between 1 and 13);
+
+alter table kins add constraint kins_num_key unique (num);
+alter table kins add constraint kins_num_check check (num between 1 and 260);
@@ -0,0 +1,51 @@
+use crate::auth::RegistrationForm;
+use crate::models::user::{Role, User};
+use crate::Db;
+use diesel::prelude::*;
+use rocket::Rocket;
+
+// `dreamspell create-admin <login> [password]`
+// makes an existing user admin or registers a new admin,
+// the only way to get the first admin on a fresh install
+pub fn run(rocket: Rocket, args: &[String]) {
+    let connection = match Db::get_one(&rocket) {
+        Some(connection) => connection,
+        None => exit("no database connection"),
+    };
+    let login = match args.first() {
+        Some(login) => login.trim(),
+        None => exit("login is missing"),
+    };
+
+    let user = match User::by_name(&connection, login).optional() {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            // same rules as the registration form
+            let form = RegistrationForm {
+                login: login.to_string(),
+                password: args.get(1).cloned().unwrap_or_default(),
+                agree: true,
+            };
+            let errors = form.validate();
+            if !errors.is_empty() {
+                exit(&errors.join(", "));
+            }
+
+            match User::insert(&connection, login, &form.password) {
+                Ok(user) => user,
+                Err(error) => exit(&error.to_string()),
+            }
+        }
+        Err(error) => exit(&error.to_string()),
+    };
+
+    match User::set_role(&connection, user.id, Role::Admin) {
+        Ok(user) => println!("{} is admin now", user.name),
+        Err(error) => exit(&error.to_string()),
+    }
+}
+
+fn exit(message: &str) -> ! {
+    println!("Create admin failed: {}", message);
+    std::process::exit(1);
+}
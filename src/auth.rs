@@ -1,28 +1,357 @@
 use crate::errors::DreamError;
+use crate::models::token::ApiToken;
+use crate::models::user::{Role, User};
+use crate::Db;
 use rocket::http::Status;
 use rocket::request::{self, FromRequest, Request};
 use rocket::Outcome;
 
 #[derive(FromForm)]
 pub struct LoginForm {
+    pub login: String,
     pub password: String,
 }
 
+pub const LOGIN_TAKEN: &str = "Пользователь с таким адресом уже зарегистрирован";
+
+#[derive(FromForm)]
+pub struct RegistrationForm {
+    pub login: String,
+    pub password: String,
+    pub agree: bool,
+}
+
+impl RegistrationForm {
+    // form errors to show on the page, uniqueness is checked separately
+    pub fn validate(&self) -> Vec<&'static str> {
+        let mut errors = vec![];
+
+        if !is_email(&self.login) {
+            errors.push("Неверный адрес эл. почты");
+        }
+        if self.password.chars().count() < 8 {
+            errors.push("Пароль должен быть не короче 8 символов");
+        }
+        if !self.agree {
+            errors.push("Необходимо согласиться с условиями");
+        }
+
+        errors
+    }
+}
+
+// token part of "Bearer <token>"
+fn bearer_token(header: &str) -> Option<&str> {
+    let mut parts = header.trim().splitn(2, ' ');
+    match (parts.next(), parts.next().map(str::trim)) {
+        (Some(scheme), Some(token))
+            if scheme.eq_ignore_ascii_case("bearer") && !token.is_empty() =>
+        {
+            Some(token)
+        }
+        _ => None,
+    }
+}
+
+// simple check: something@domain.zone
+fn is_email(login: &str) -> bool {
+    let mut parts = login.split('@');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(user), Some(domain), None) => {
+            !user.is_empty()
+                && !login.contains(char::is_whitespace)
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+        }
+        _ => false,
+    }
+}
+
+#[derive(FromForm)]
+pub struct RoleForm {
+    pub role: Role,
+}
+
+#[derive(FromForm)]
+pub struct TokenForm {
+    pub name: String,
+}
+
+// user management
+#[derive(Debug)]
+pub struct Admin(i32);
+
+// content editing, admins are editors too
+#[derive(Debug)]
+pub struct Editor(pub i32);
+
+// any logged in user
+#[derive(Debug)]
+pub struct Member(pub i32);
+
+// content editing from scripts: "Authorization: Bearer <token>"
 #[derive(Debug)]
-pub struct Admin(usize);
+pub struct ApiEditor(i32);
 
 impl<'a, 'r> FromRequest<'a, 'r> for Admin {
     type Error = DreamError;
 
     fn from_request(request: &'a Request<'r>) -> request::Outcome<Admin, DreamError> {
-        match request
-            .cookies()
-            .get_private("admin")
-            .and_then(|cookie| cookie.value().parse().ok())
-            .map(|id| Admin(id))
+        user_with_role(request, &[Role::Admin]).map(|user| Admin(user.id))
+    }
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for Editor {
+    type Error = DreamError;
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Editor, DreamError> {
+        user_with_role(request, &[Role::Admin, Role::Editor]).map(|user| Editor(user.id))
+    }
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for Member {
+    type Error = DreamError;
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Member, DreamError> {
+        user_with_role(request, &[Role::Admin, Role::Editor, Role::Member])
+            .map(|user| Member(user.id))
+    }
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for ApiEditor {
+    type Error = DreamError;
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<ApiEditor, DreamError> {
+        // no need for the database without a token
+        let token = match request
+            .headers()
+            .get_one("Authorization")
+            .and_then(bearer_token)
         {
-            Some(id) => Outcome::Success(id),
+            Some(token) => token,
+            None => return Outcome::Failure((Status::Unauthorized, DreamError::Unauthorized)),
+        };
+        let connection = match request.guard::<Db>() {
+            Outcome::Success(connection) => connection,
+            _ => {
+                return Outcome::Failure((
+                    Status::InternalServerError,
+                    DreamError::InternalServerError,
+                ))
+            }
+        };
+
+        match ApiToken::user(&connection, token).ok() {
+            Some(user) => match user.role() {
+                Role::Admin | Role::Editor => Outcome::Success(ApiEditor(user.id)),
+                Role::Member => Outcome::Failure((Status::Forbidden, DreamError::Forbidden)),
+            },
             None => Outcome::Failure((Status::Unauthorized, DreamError::Unauthorized)),
         }
     }
 }
+
+fn user_with_role(request: &Request, roles: &[Role]) -> request::Outcome<User, DreamError> {
+    // cookie keeps user id, no need for the database without it
+    let id: i32 = match request
+        .cookies()
+        .get_private("user")
+        .and_then(|cookie| cookie.value().parse().ok())
+    {
+        Some(id) => id,
+        None => return Outcome::Failure((Status::Unauthorized, DreamError::Unauthorized)),
+    };
+    let connection = match request.guard::<Db>() {
+        Outcome::Success(connection) => connection,
+        _ => {
+            return Outcome::Failure((Status::InternalServerError, DreamError::InternalServerError))
+        }
+    };
+
+    // the user should still exist
+    match User::get(&connection, id).ok() {
+        Some(user) => {
+            if roles.contains(&user.role()) {
+                Outcome::Success(user)
+            } else {
+                Outcome::Failure((Status::Forbidden, DreamError::Forbidden))
+            }
+        }
+        None => Outcome::Failure((Status::Unauthorized, DreamError::Unauthorized)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::http::Header;
+    use rocket::local::Client;
+
+    #[get("/")]
+    fn guarded(_editor: ApiEditor) -> &'static str {
+        "ok"
+    }
+
+    // admin lists behind the role guards, with a database if there is one
+    fn admin_client(url: Option<&str>) -> Client {
+        use rocket::config::{Config, Environment, Table};
+
+        let mut rocket = match url {
+            Some(url) => {
+                let mut database = Table::new();
+                database.insert("url".to_string(), url.into());
+                let mut databases = Table::new();
+                databases.insert("dreamspell".to_string(), database.into());
+                let config = Config::build(Environment::Development)
+                    .extra("databases", databases)
+                    .finalize()
+                    .unwrap();
+
+                rocket::custom(config).attach(Db::fairing())
+            }
+            None => rocket::ignite(),
+        };
+        rocket = rocket
+            .mount("/admin/glyphs", routes![crate::views::admin::glyphs::list])
+            .mount("/admin/users", routes![crate::views::admin::users::list]);
+
+        Client::new(rocket).unwrap()
+    }
+
+    #[test]
+    fn admin_without_cookie() {
+        // no database at all: the guard answers before asking for it
+        let client = admin_client(None);
+        for uri in &["/admin/glyphs", "/admin/users"] {
+            let response = client.get(*uri).dispatch();
+            assert_eq!(response.status(), Status::Unauthorized, "{}", uri);
+        }
+    }
+
+    // needs a migrated database in DATABASE_URL, skipped otherwise
+    #[test]
+    fn admin_roles() {
+        use crate::models::schema::users;
+        use diesel::prelude::*;
+        use rocket::http::Cookie;
+
+        let url = match std::env::var("DATABASE_URL") {
+            Ok(url) => url,
+            Err(_) => {
+                println!("admin_roles skipped: DATABASE_URL is not set");
+                return;
+            }
+        };
+        let connection = PgConnection::establish(&url).unwrap();
+        let user = |role: Role| -> i32 {
+            let name = format!("{}-{}@roles.test", role.as_str(), std::process::id());
+            diesel::insert_into(users::table)
+                .values((
+                    users::name.eq(name),
+                    users::password.eq(""),
+                    users::role.eq(role.as_str()),
+                ))
+                .returning(users::id)
+                .get_result(&connection)
+                .unwrap()
+        };
+        let member = user(Role::Member);
+        let editor = user(Role::Editor);
+
+        let client = admin_client(Some(&url));
+        let status = |uri: &str, id: i32| {
+            client
+                .get(uri.to_string())
+                .private_cookie(Cookie::new("user", id.to_string()))
+                .dispatch()
+                .status()
+        };
+        let statuses = (
+            status("/admin/glyphs", member),
+            status("/admin/users", editor),
+            status("/admin/glyphs", editor),
+        );
+
+        diesel::delete(users::table.filter(users::id.eq_any(vec![member, editor])))
+            .execute(&connection)
+            .unwrap();
+        assert_eq!(statuses.0, Status::Forbidden);
+        assert_eq!(statuses.1, Status::Forbidden);
+        assert_ne!(statuses.2, Status::Forbidden);
+    }
+
+    #[test]
+    fn bearer_tokens() {
+        assert_eq!(bearer_token("Bearer abc"), Some("abc"));
+        assert_eq!(bearer_token("bearer abc"), Some("abc"));
+        assert_eq!(bearer_token("BEARER abc"), Some("abc"));
+        assert_eq!(bearer_token("  Bearer   abc  "), Some("abc"));
+        assert_eq!(bearer_token("Bearer"), None);
+        assert_eq!(bearer_token("Bearer   "), None);
+        assert_eq!(bearer_token("Bearerabc"), None);
+        assert_eq!(bearer_token("Basic YTpi"), None);
+        assert_eq!(bearer_token(""), None);
+        assert_eq!(bearer_token("Беарер abc"), None);
+        assert_eq!(bearer_token("Bearer токен"), Some("токен"));
+    }
+
+    #[test]
+    fn api_editor_without_token() {
+        let client = Client::new(rocket::ignite().mount("/", routes![guarded])).unwrap();
+
+        let response = client.get("/").dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+
+        for value in &[
+            "Basic YTpi",
+            "Bearer",
+            "Bearer   ",
+            "token abc",
+            "Беарер abc",
+        ] {
+            let response = client
+                .get("/")
+                .header(Header::new("Authorization", *value))
+                .dispatch();
+            assert_eq!(response.status(), Status::Unauthorized, "{}", value);
+        }
+    }
+
+    fn form(login: &str, password: &str, agree: bool) -> RegistrationForm {
+        RegistrationForm {
+            login: login.to_string(),
+            password: password.to_string(),
+            agree,
+        }
+    }
+
+    #[test]
+    fn emails() {
+        assert!(is_email("a@b.c"));
+        assert!(is_email("first.last@mail.example.ru"));
+        assert!(!is_email(""));
+        assert!(!is_email("ab.c"));
+        assert!(!is_email("@b.c"));
+        assert!(!is_email("a@bc"));
+        assert!(!is_email("a@.bc"));
+        assert!(!is_email("a@bc."));
+        assert!(!is_email("a@b@c.d"));
+        assert!(!is_email("a b@c.d"));
+    }
+
+    #[test]
+    fn registration() {
+        assert!(form("new@b.ru", "longenough", true).validate().is_empty());
+        // eight characters, not bytes
+        assert!(form("new@b.ru", "пароль12", true).validate().is_empty());
+        assert_eq!(
+            form("new", "short", false).validate(),
+            vec![
+                "Неверный адрес эл. почты",
+                "Пароль должен быть не короче 8 символов",
+                "Необходимо согласиться с условиями",
+            ]
+        );
+    }
+}
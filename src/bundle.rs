@@ -0,0 +1,353 @@
+use crate::errors::DreamError;
+use crate::models::content::{delete_unused_image, Content};
+use crate::models::form::{has_image_extension, MultipartModel, IMAGE_SIZE_LIMIT};
+use crate::models::glyph::{Glyph, NewGlyph};
+use crate::models::kin::{Kin, NewKin};
+use crate::models::tone::{NewTone, Tone};
+use crate::models::utils::{file_exists, is_plain_name, read_file, write_file};
+use crate::{Db, DreamResult};
+use diesel::prelude::*;
+use rocket::Rocket;
+use std::collections::BTreeSet;
+use std::io::{Cursor, Read, Write};
+use zip::{write::FileOptions, ZipArchive, ZipWriter};
+
+// bundle layout:
+// glyphs.json, tones.json, kins.json - full rows, ids are ignored on import,
+// rows are matched by num
+// images/<file name> - images from static/upload referenced by the rows
+
+// what to do with rows and images that already exist
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Conflict {
+    Skip,
+    Overwrite,
+}
+
+#[derive(Serialize, Default)]
+pub struct TableImport {
+    pub title: &'static str,
+    pub created: usize,
+    pub updated: usize,
+    pub skipped: usize,
+    // nums of rows that didn't pass validation
+    pub invalid: Vec<i32>,
+}
+
+#[derive(Serialize, Default)]
+pub struct ImportReport {
+    pub tables: Vec<TableImport>,
+    pub images: usize,
+    // referenced but found neither in the bundle nor in static/upload
+    pub missing_images: Vec<String>,
+    // bundle images that were not written: unused, not jpg/png or too big
+    pub rejected_images: Vec<String>,
+}
+
+// rows are matched by num: new ones are inserted,
+// existing ones are skipped or updated depending on the conflict mode,
+// images replaced by the update are collected to be deleted after commit
+fn import_table<T: Content>(
+    connection: &PgConnection,
+    items: &[T::New],
+    title: &'static str,
+    conflict: Conflict,
+    superseded: &mut Vec<String>,
+) -> QueryResult<TableImport> {
+    let mut table = TableImport {
+        title,
+        ..TableImport::default()
+    };
+    for item in items.iter().cloned() {
+        if !item.validate().is_empty() || !(item.image().is_empty() || is_plain_name(item.image()))
+        {
+            table.invalid.push(item.num());
+            continue;
+        }
+
+        match T::by_num(connection, item.num()).optional()? {
+            None => {
+                T::insert(connection, item)?;
+                table.created += 1;
+            }
+            Some(old) if conflict == Conflict::Overwrite => {
+                let (old, new) = T::update_row(connection, item, old.id())?;
+                if new.image() != old.image() {
+                    superseded.push(old.image().to_string());
+                }
+                table.updated += 1;
+            }
+            Some(_) => table.skipped += 1,
+        }
+    }
+
+    Ok(table)
+}
+
+pub fn export(connection: &PgConnection) -> DreamResult<Vec<u8>> {
+    let glyphs = Glyph::all(connection)?;
+    let tones = Tone::all(connection)?;
+    let kins = Kin::all(connection)?;
+
+    let images: BTreeSet<&str> = glyphs
+        .iter()
+        .map(|glyph| glyph.image.as_str())
+        .chain(tones.iter().map(|tone| tone.image.as_str()))
+        .chain(kins.iter().map(|kin| kin.image.as_str()))
+        .filter(|image| !image.is_empty())
+        .collect();
+
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    write_entry(&mut writer, "glyphs.json", &to_json(&glyphs)?)?;
+    write_entry(&mut writer, "tones.json", &to_json(&tones)?)?;
+    write_entry(&mut writer, "kins.json", &to_json(&kins)?)?;
+    for image in images {
+        match read_file(image) {
+            Some(bytes) => write_entry(&mut writer, &format!("images/{}", image), &bytes)?,
+            None => println!("Export: image {} not found", image),
+        }
+    }
+
+    let cursor = writer.finish().map_err(|error| {
+        println!("Export error: {}", error);
+        DreamError::InternalServerError
+    })?;
+
+    Ok(cursor.into_inner())
+}
+
+// everything or nothing: rows are imported in one transaction
+pub fn import(
+    connection: &PgConnection,
+    bytes: &[u8],
+    conflict: Conflict,
+) -> DreamResult<ImportReport> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).map_err(bad_bundle)?;
+    let glyphs: Vec<NewGlyph> = from_json(&read_entry(&mut archive, "glyphs.json")?)?;
+    let tones: Vec<NewTone> = from_json(&read_entry(&mut archive, "tones.json")?)?;
+    let kins: Vec<NewKin> = from_json(&read_entry(&mut archive, "kins.json")?)?;
+
+    let mut report = ImportReport::default();
+    let mut superseded = Vec::new();
+    connection.transaction::<_, DreamError, _>(|| {
+        report.tables = vec![
+            import_table::<Glyph>(connection, &glyphs, "Печати", conflict, &mut superseded)?,
+            import_table::<Tone>(connection, &tones, "Тона", conflict, &mut superseded)?,
+            import_table::<Kin>(connection, &kins, "Кины", conflict, &mut superseded)?,
+        ];
+
+        Ok(())
+    })?;
+
+    // files are touched only after the rows are in
+    let referenced: BTreeSet<String> = glyphs
+        .iter()
+        .map(|glyph| glyph.image.clone())
+        .chain(tones.iter().map(|tone| tone.image.clone()))
+        .chain(kins.iter().map(|kin| kin.image.clone()))
+        .filter(|image| !image.is_empty() && is_plain_name(image))
+        .collect();
+    let mut in_bundle = BTreeSet::new();
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index).map_err(bad_bundle)?;
+        let image = match image_name(file.name()) {
+            Some(image) => image,
+            None => continue,
+        };
+        if !is_importable(&image, file.size(), &referenced) {
+            report.rejected_images.push(image);
+            continue;
+        }
+        if conflict == Conflict::Skip && file_exists(&image) {
+            in_bundle.insert(image);
+            continue;
+        }
+
+        // the declared size is not trusted while unpacking
+        let mut bytes = Vec::new();
+        (&mut file)
+            .take(IMAGE_SIZE_LIMIT + 1)
+            .read_to_end(&mut bytes)
+            .map_err(|error| {
+                println!("Import error: {}", error);
+                DreamError::BadRequest
+            })?;
+        if bytes.len() as u64 > IMAGE_SIZE_LIMIT {
+            report.rejected_images.push(image);
+            continue;
+        }
+        in_bundle.insert(image.clone());
+        write_file(&image, &bytes).map_err(|error| {
+            println!("Import error: {}", error);
+            DreamError::InternalServerError
+        })?;
+        report.images += 1;
+    }
+    report.missing_images = referenced
+        .iter()
+        .filter(|image| !in_bundle.contains(*image) && !file_exists(image))
+        .cloned()
+        .collect();
+
+    // old images of updated rows, unless the bundle or other rows still use them
+    for image in superseded {
+        if !referenced.contains(&image) && !in_bundle.contains(&image) {
+            delete_unused_image(connection, &image)?;
+        }
+    }
+
+    Ok(report)
+}
+
+// `dreamspell export <file>` and `dreamspell import <file> [--overwrite]`
+pub fn run(rocket: Rocket, command: &str, args: &[String]) {
+    let connection = match Db::get_one(&rocket) {
+        Some(connection) => connection,
+        None => exit("no database connection"),
+    };
+    let path = match args.first() {
+        Some(path) => path,
+        None => exit("bundle file is missing"),
+    };
+
+    if command == "export" {
+        match export(&connection).map(|bytes| std::fs::write(path, bytes)) {
+            Ok(Ok(())) => println!("Exported to {}", path),
+            Ok(Err(error)) => exit(&error.to_string()),
+            Err(error) => exit(&error.to_string()),
+        }
+    } else {
+        let conflict = if args.iter().any(|arg| arg == "--overwrite") {
+            Conflict::Overwrite
+        } else {
+            Conflict::Skip
+        };
+        let bytes = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(error) => exit(&error.to_string()),
+        };
+
+        match import(&connection, &bytes, conflict) {
+            Ok(report) => {
+                for table in report.tables {
+                    println!(
+                        "{}: created {}, updated {}, skipped {}, invalid {:?}",
+                        table.title, table.created, table.updated, table.skipped, table.invalid
+                    );
+                }
+                println!("Images: {}", report.images);
+                if !report.missing_images.is_empty() {
+                    println!("Missing images: {:?}", report.missing_images);
+                }
+                if !report.rejected_images.is_empty() {
+                    println!("Rejected images: {:?}", report.rejected_images);
+                }
+            }
+            Err(error) => exit(&error.to_string()),
+        }
+    }
+}
+
+fn exit(message: &str) -> ! {
+    println!("Bundle failed: {}", message);
+    std::process::exit(1);
+}
+
+fn bad_bundle(error: zip::result::ZipError) -> DreamError {
+    println!("Bundle error: {}", error);
+    DreamError::BadRequest
+}
+
+fn to_json<T: serde::Serialize>(items: &T) -> DreamResult<Vec<u8>> {
+    serde_json::to_vec_pretty(items).map_err(|error| {
+        println!("Export error: {}", error);
+        DreamError::InternalServerError
+    })
+}
+
+fn from_json<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> DreamResult<T> {
+    serde_json::from_slice(bytes).map_err(|error| {
+        println!("Bundle error: {}", error);
+        DreamError::BadRequest
+    })
+}
+
+fn write_entry(
+    writer: &mut ZipWriter<Cursor<Vec<u8>>>,
+    name: &str,
+    bytes: &[u8],
+) -> DreamResult<()> {
+    writer
+        .start_file(name, FileOptions::default())
+        .map_err(|error| {
+            println!("Export error: {}", error);
+            DreamError::InternalServerError
+        })?;
+    writer.write_all(bytes).map_err(|error| {
+        println!("Export error: {}", error);
+        DreamError::InternalServerError
+    })
+}
+
+fn read_entry(archive: &mut ZipArchive<Cursor<&[u8]>>, name: &str) -> DreamResult<Vec<u8>> {
+    let mut file = archive.by_name(name).map_err(bad_bundle)?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).map_err(|error| {
+        println!("Bundle error: {}", error);
+        DreamError::BadRequest
+    })?;
+
+    Ok(bytes)
+}
+
+// plain file name from "images/<name>", nothing for folders or paths
+fn image_name(entry: &str) -> Option<String> {
+    if !entry.starts_with("images/") {
+        return None;
+    }
+    let name = &entry["images/".len()..];
+    if !is_plain_name(name) {
+        return None;
+    }
+
+    Some(name.to_string())
+}
+
+// same rules as for uploaded images, and only what the rows use
+fn is_importable(image: &str, size: u64, referenced: &BTreeSet<String>) -> bool {
+    referenced.contains(image) && has_image_extension(image) && size <= IMAGE_SIZE_LIMIT
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn image_names() {
+        assert_eq!(image_name("images/hand.png"), Some("hand.png".to_string()));
+        assert_eq!(image_name("images/"), None);
+        assert_eq!(image_name("images/../evil.png"), None);
+        assert_eq!(image_name("images/sub/hand.png"), None);
+        assert_eq!(image_name("images/.hidden"), None);
+        assert_eq!(image_name("glyphs.json"), None);
+        assert_eq!(image_name("hand.png"), None);
+    }
+
+    #[test]
+    fn importable_images() {
+        let referenced: BTreeSet<String> = vec!["hand.png", "x.html", "moon.JPG"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        assert!(is_importable("hand.png", 1024, &referenced));
+        assert!(is_importable("moon.JPG", IMAGE_SIZE_LIMIT, &referenced));
+        assert!(!is_importable("other.png", 1024, &referenced));
+        assert!(!is_importable("x.html", 1024, &referenced));
+        assert!(!is_importable(
+            "hand.png",
+            IMAGE_SIZE_LIMIT + 1,
+            &referenced
+        ));
+    }
+}
@@ -0,0 +1,137 @@
+use super::signature::Signature;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Color {
+    Red,
+    White,
+    Blue,
+    Yellow,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Family {
+    Polar,
+    Cardinal,
+    Core,
+    Signal,
+    Gateway,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Clan {
+    Fire,
+    Blood,
+    Truth,
+    Sky,
+    Earth,
+}
+
+// derived attributes of a kin
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+pub struct Classification {
+    // 1-5, 52 kins each: red, white, blue, yellow and green castle
+    pub castle: i32,
+    pub color: Color,
+    pub family: Family,
+    pub clan: Clan,
+    // galactic activation portal
+    pub portal: bool,
+}
+
+impl Classification {
+    pub fn new(signature: Signature) -> Classification {
+        let seal = signature.seal;
+
+        Classification {
+            castle: (signature.kin - 1) / 52 + 1,
+            color: match seal % 4 {
+                1 => Color::Red,
+                2 => Color::White,
+                3 => Color::Blue,
+                _ => Color::Yellow,
+            },
+            family: match seal % 5 {
+                0 => Family::Polar,
+                1 => Family::Cardinal,
+                2 => Family::Core,
+                3 => Family::Signal,
+                _ => Family::Gateway,
+            },
+            // each clan starts with yellow seal: sun, seed, star, human, warrior
+            clan: match seal % 20 / 4 {
+                0 => Clan::Fire,
+                1 => Clan::Blood,
+                2 => Clan::Truth,
+                3 => Clan::Sky,
+                _ => Clan::Earth,
+            },
+            portal: is_portal(signature.kin),
+        }
+    }
+}
+
+// loom of maya: diagonals of the harmonic module, mystic column has no portals,
+// the pattern is symmetric - fold it into the upper left quarter
+pub fn is_portal(kin: i32) -> bool {
+    let column = (kin - 1) / 20;
+    let row = (kin - 1) % 20;
+    let column = column.min(12 - column);
+    let row = row.min(19 - row);
+
+    column < 6 && (row == column || row + column == 11 || (column == 5 && row >= 5))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::KINS;
+
+    const PORTALS: [i32; 52] = [
+        1, 20, 22, 39, 43, 50, 51, 58, 64, 69, 72, 77, 85, 88, 93, 96, 106, 107, 108, 109, 110,
+        111, 112, 113, 114, 115, 146, 147, 148, 149, 150, 151, 152, 153, 154, 155, 165, 168, 173,
+        176, 184, 189, 192, 197, 203, 210, 211, 218, 222, 239, 241, 260,
+    ];
+
+    fn classification(kin: i32) -> Classification {
+        Classification::new(Signature::new(kin).unwrap())
+    }
+
+    #[test]
+    fn portals() {
+        for kin in 1..=KINS {
+            assert_eq!(is_portal(kin), PORTALS.contains(&kin), "kin {}", kin);
+        }
+    }
+
+    #[test]
+    fn castles() {
+        assert_eq!(classification(1).castle, 1);
+        assert_eq!(classification(52).castle, 1);
+        assert_eq!(classification(53).castle, 2);
+        assert_eq!(classification(260).castle, 5);
+    }
+
+    #[test]
+    fn known_kins() {
+        // blue crystal hand
+        let hand = classification(207);
+        assert_eq!(hand.color, Color::Blue);
+        assert_eq!(hand.family, Family::Core);
+        assert_eq!(hand.clan, Clan::Blood);
+        assert_eq!(hand.castle, 4);
+        assert!(!hand.portal);
+
+        // yellow cosmic sun
+        let sun = classification(260);
+        assert_eq!(sun.color, Color::Yellow);
+        assert_eq!(sun.family, Family::Polar);
+        assert_eq!(sun.clan, Clan::Fire);
+        assert!(sun.portal);
+
+        // year bearers are the gateway family
+        assert_eq!(classification(164).family, Family::Gateway);
+    }
+}
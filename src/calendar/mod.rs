@@ -0,0 +1,19 @@
+pub mod classification;
+pub mod moon;
+pub mod oracle;
+pub mod relationship;
+pub mod signature;
+pub mod wavespell;
+pub mod year;
+
+// number of days in the tzolkin
+pub const KINS: i32 = 260;
+// number of solar seals
+pub const SEALS: i32 = 20;
+// number of galactic tones
+pub const TONES: i32 = 13;
+
+// keep seal number in 1-20 range
+pub fn wrap_seal(seal: i32) -> i32 {
+    (seal - 1).rem_euclid(SEALS) + 1
+}
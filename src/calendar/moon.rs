@@ -0,0 +1,182 @@
+use super::signature::{day_count, is_hunab_ku};
+use chrono::{Datelike, Duration, NaiveDate};
+
+pub const MOONS: i32 = 13;
+pub const MOON_DAYS: i32 = 28;
+
+// moon names with their totems
+const MOON_NAMES: [(&str, &str); 13] = [
+    ("Магнитная", "Летучая мышь"),
+    ("Лунная", "Скорпион"),
+    ("Электрическая", "Олень"),
+    ("Самосущная", "Сова"),
+    ("Обертонная", "Павлин"),
+    ("Ритмическая", "Ящерица"),
+    ("Резонансная", "Обезьяна"),
+    ("Галактическая", "Ястреб"),
+    ("Солнечная", "Ягуар"),
+    ("Планетарная", "Собака"),
+    ("Спектральная", "Змея"),
+    ("Кристальная", "Кролик"),
+    ("Космическая", "Черепаха"),
+];
+
+// days of the week
+const PLASMAS: [&str; 7] = ["Дали", "Сели", "Гамма", "Кали", "Альфа", "Лими", "Силио"];
+
+// date in the 13-moon calendar,
+// year is the gregorian year when 13-moon year starts (26 july)
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(tag = "kind")]
+pub enum MoonDate {
+    Day {
+        year: i32,
+        moon: i32,
+        day: i32,
+        name: &'static str,
+        totem: &'static str,
+        plasma: &'static str,
+    },
+    // 25 july
+    DayOutOfTime {
+        year: i32,
+    },
+    // 29 february - 0.0 hunab ku
+    HunabKu {
+        year: i32,
+    },
+}
+
+impl MoonDate {
+    // moon 1-13, day of moon 1-28
+    pub fn day(year: i32, moon: i32, day: i32) -> Option<MoonDate> {
+        if moon < 1 || moon > MOONS || day < 1 || day > MOON_DAYS {
+            return None;
+        }
+
+        let (name, totem) = MOON_NAMES[(moon - 1) as usize];
+        Some(MoonDate::Day {
+            year,
+            moon,
+            day,
+            name,
+            totem,
+            plasma: PLASMAS[((day - 1) % 7) as usize],
+        })
+    }
+
+    pub fn from_date(date: NaiveDate) -> MoonDate {
+        let year = year_of(date);
+
+        if date.month() == 7 && date.day() == 25 {
+            return MoonDate::DayOutOfTime { year };
+        }
+        if is_hunab_ku(date) {
+            return MoonDate::HunabKu { year };
+        }
+
+        // 26 july of the previous year exists for every date but the earliest ones
+        let start = year_start(year).expect("13-moon year start out of range");
+        let index = (day_count(date) - day_count(start)) as i32;
+        // index is always in 0..364 here
+        Self::day(year, index / MOON_DAYS + 1, index % MOON_DAYS + 1).unwrap()
+    }
+
+    // back to gregorian, none for hunab ku in non leap year
+    // and for years out of the supported range
+    pub fn to_date(&self) -> Option<NaiveDate> {
+        match *self {
+            MoonDate::Day {
+                year, moon, day, ..
+            } => {
+                let index = (moon - 1) * MOON_DAYS + day - 1;
+                let date =
+                    year_start(year)?.checked_add_signed(Duration::days(i64::from(index)))?;
+                // 29 february is not counted - skip it
+                match NaiveDate::from_ymd_opt(year.checked_add(1)?, 2, 29) {
+                    Some(hunab_ku) if date >= hunab_ku => {
+                        date.checked_add_signed(Duration::days(1))
+                    }
+                    _ => Some(date),
+                }
+            }
+            MoonDate::DayOutOfTime { year } => NaiveDate::from_ymd_opt(year.checked_add(1)?, 7, 25),
+            MoonDate::HunabKu { year } => NaiveDate::from_ymd_opt(year.checked_add(1)?, 2, 29),
+        }
+    }
+}
+
+// 13-moon year of the date
+pub fn year_of(date: NaiveDate) -> i32 {
+    if (date.month(), date.day()) >= (7, 26) {
+        date.year()
+    } else {
+        date.year() - 1
+    }
+}
+
+// none for years chrono can't represent
+pub fn year_start(year: i32) -> Option<NaiveDate> {
+    NaiveDate::from_ymd_opt(year, 7, 26)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn moon_day(date: NaiveDate) -> Option<(i32, i32, i32)> {
+        match MoonDate::from_date(date) {
+            MoonDate::Day {
+                year, moon, day, ..
+            } => Some((year, moon, day)),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn known_dates() {
+        let ymd = NaiveDate::from_ymd;
+        assert_eq!(moon_day(ymd(2012, 7, 26)), Some((2012, 1, 1)));
+        assert_eq!(moon_day(ymd(2012, 12, 21)), Some((2012, 6, 9)));
+        assert_eq!(moon_day(ymd(2013, 2, 7)), Some((2012, 8, 1)));
+        assert_eq!(moon_day(ymd(2020, 3, 1)), Some((2019, 8, 23)));
+        assert_eq!(moon_day(ymd(2013, 7, 24)), Some((2012, 13, 28)));
+        assert_eq!(
+            MoonDate::from_date(ymd(2013, 7, 25)),
+            MoonDate::DayOutOfTime { year: 2012 }
+        );
+        assert_eq!(
+            MoonDate::from_date(ymd(2020, 2, 29)),
+            MoonDate::HunabKu { year: 2019 }
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut date = NaiveDate::from_ymd(1999, 7, 1);
+        while date < NaiveDate::from_ymd(2005, 8, 1) {
+            assert_eq!(MoonDate::from_date(date).to_date(), Some(date));
+            date = date.succ();
+        }
+    }
+
+    #[test]
+    fn out_of_range_years() {
+        assert_eq!(MoonDate::day(300_000, 1, 1).unwrap().to_date(), None);
+        assert_eq!(MoonDate::day(i32::MAX, 13, 28).unwrap().to_date(), None);
+        assert_eq!(MoonDate::DayOutOfTime { year: i32::MAX }.to_date(), None);
+        assert_eq!(MoonDate::HunabKu { year: i32::MAX }.to_date(), None);
+        assert_eq!(year_start(300_000), None);
+    }
+
+    #[test]
+    fn plasmas() {
+        if let Some(MoonDate::Day { plasma, .. }) = MoonDate::day(2020, 3, 15) {
+            assert_eq!(plasma, "Дали");
+        } else {
+            panic!("valid moon day");
+        }
+        assert_eq!(MoonDate::day(2020, 14, 1), None);
+        assert_eq!(MoonDate::day(2020, 1, 29), None);
+    }
+}
@@ -0,0 +1,99 @@
+use super::signature::Signature;
+use super::wrap_seal;
+
+// fifth-force oracle of a kin
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+pub struct Oracle {
+    pub destiny: Signature,
+    pub guide: Signature,
+    pub analog: Signature,
+    pub antipode: Signature,
+    pub occult: Signature,
+}
+
+impl Oracle {
+    pub fn new(destiny: Signature) -> Oracle {
+        let Signature { seal, tone, .. } = destiny;
+
+        Oracle {
+            destiny,
+            guide: Signature::from_seal_and_tone(guide_seal(seal, tone), tone),
+            analog: Signature::from_seal_and_tone(wrap_seal(19 - seal), tone),
+            antipode: Signature::from_seal_and_tone(wrap_seal(seal + 10), tone),
+            occult: Signature::from_seal_and_tone(wrap_seal(21 - seal), 14 - tone),
+        }
+    }
+}
+
+// guide has the same color, the shift depends on tone
+fn guide_seal(seal: i32, tone: i32) -> i32 {
+    let shift = match tone % 5 {
+        1 => 0,
+        2 => 12,
+        3 => 4,
+        4 => 16,
+        _ => 8,
+    };
+
+    wrap_seal(seal + shift)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::KINS;
+
+    fn oracles() -> Vec<Oracle> {
+        (1..=KINS)
+            .map(|kin| Oracle::new(Signature::new(kin).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn analog() {
+        for oracle in oracles() {
+            assert_eq!((oracle.destiny.seal + oracle.analog.seal) % 20, 19);
+            assert_eq!(oracle.destiny.tone, oracle.analog.tone);
+        }
+    }
+
+    #[test]
+    fn antipode() {
+        for oracle in oracles() {
+            assert_eq!((oracle.destiny.seal - oracle.antipode.seal).abs(), 10);
+            assert_eq!(oracle.destiny.tone, oracle.antipode.tone);
+        }
+    }
+
+    #[test]
+    fn occult() {
+        for oracle in oracles() {
+            assert_eq!(oracle.destiny.kin + oracle.occult.kin, 261);
+            assert_eq!(oracle.destiny.seal + oracle.occult.seal, 21);
+            assert_eq!(oracle.destiny.tone + oracle.occult.tone, 14);
+        }
+    }
+
+    #[test]
+    fn guide() {
+        for oracle in oracles() {
+            // same color, same tone
+            assert_eq!(oracle.destiny.seal % 4, oracle.guide.seal % 4);
+            assert_eq!(oracle.destiny.tone, oracle.guide.tone);
+            // dots 1, 6, 11 - kin is its own guide
+            if oracle.destiny.tone % 5 == 1 {
+                assert_eq!(oracle.destiny, oracle.guide);
+            }
+        }
+    }
+
+    #[test]
+    fn known_oracle() {
+        // kin 207 - blue crystal hand
+        let oracle = Oracle::new(Signature::new(207).unwrap());
+        assert_eq!(oracle.guide.kin, 259);
+        assert_eq!(oracle.analog.kin, 12);
+        assert_eq!(oracle.antipode.kin, 77);
+        assert_eq!(oracle.occult.kin, 54);
+    }
+}
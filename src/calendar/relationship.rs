@@ -0,0 +1,104 @@
+use super::classification::Classification;
+use super::signature::Signature;
+use super::wavespell::Wavespell;
+use super::{wrap_seal, KINS};
+
+// how two kins relate to each other
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+pub struct Relationship {
+    pub first: Signature,
+    pub second: Signature,
+    pub same_wavespell: bool,
+    // partners are found by seals, tones don't matter
+    pub analog: bool,
+    pub antipode: bool,
+    pub occult: bool,
+    pub same_color: bool,
+    pub same_family: bool,
+    // sum of both kins
+    pub composite: Signature,
+}
+
+impl Relationship {
+    pub fn new(first: Signature, second: Signature) -> Relationship {
+        let first_class = Classification::new(first);
+        let second_class = Classification::new(second);
+
+        Relationship {
+            first,
+            second,
+            same_wavespell: Wavespell::new(first).number == Wavespell::new(second).number,
+            analog: wrap_seal(19 - first.seal) == second.seal,
+            antipode: wrap_seal(first.seal + 10) == second.seal,
+            occult: first.seal + second.seal == 21,
+            same_color: first_class.color == second_class.color,
+            same_family: first_class.family == second_class.family,
+            composite: composite(first, second),
+        }
+    }
+}
+
+fn composite(first: Signature, second: Signature) -> Signature {
+    let kin = (first.kin + second.kin - 1) % KINS + 1;
+
+    // always in 1-260 range
+    Signature::new(kin).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relationship(first: i32, second: i32) -> Relationship {
+        Relationship::new(
+            Signature::new(first).unwrap(),
+            Signature::new(second).unwrap(),
+        )
+    }
+
+    #[test]
+    fn composite_kin() {
+        assert_eq!(relationship(1, 2).composite.kin, 3);
+        assert_eq!(relationship(200, 60).composite.kin, 260);
+        assert_eq!(relationship(207, 164).composite.kin, 111);
+        assert_eq!(relationship(260, 260).composite.kin, 260);
+    }
+
+    #[test]
+    fn partners() {
+        // blue hand and yellow human are analogs
+        let analogs = relationship(207, 12);
+        assert!(analogs.analog);
+        assert!(!analogs.antipode);
+        assert!(!analogs.occult);
+
+        // blue hand and red earth are antipodes
+        assert!(relationship(207, 17).antipode);
+        // blue hand and white wizard are occult
+        assert!(relationship(207, 54).occult);
+        // relationship works both ways
+        for first in 1..=KINS {
+            for &second in [1, 77, 130, 260].iter() {
+                let there = relationship(first, second);
+                let back = relationship(second, first);
+                assert_eq!(there.analog, back.analog);
+                assert_eq!(there.antipode, back.antipode);
+                assert_eq!(there.occult, back.occult);
+                assert_eq!(there.composite, back.composite);
+            }
+        }
+    }
+
+    #[test]
+    fn families() {
+        let same = relationship(207, 196);
+        assert!(same.same_wavespell);
+        assert!(!same.same_color);
+
+        // red dragon and red serpent
+        let reds = relationship(1, 5);
+        assert!(reds.same_color);
+        assert!(!reds.same_family);
+        assert!(reds.same_wavespell);
+    }
+}
@@ -0,0 +1,148 @@
+use super::{KINS, SEALS, TONES};
+use chrono::{Datelike, NaiveDate};
+
+// 26 july 1987 is kin 34 (white galactic wizard) - dreamspell starting point
+const EPOCH_KIN: i32 = 34;
+
+// galactic signature of a day: kin 1-260 with its seal 1-20 and tone 1-13
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+pub struct Signature {
+    pub kin: i32,
+    pub seal: i32,
+    pub tone: i32,
+}
+
+impl Signature {
+    pub fn new(kin: i32) -> Option<Signature> {
+        if kin < 1 || kin > KINS {
+            return None;
+        }
+
+        Some(Signature {
+            kin,
+            seal: (kin - 1) % SEALS + 1,
+            tone: (kin - 1) % TONES + 1,
+        })
+    }
+
+    pub fn from_seal_and_tone(seal: i32, tone: i32) -> Signature {
+        // 40 is 1 by modulo 13 and 0 by modulo 20, -39 is the other way around
+        let kin = (40 * tone - 39 * seal).rem_euclid(KINS);
+        let kin = if kin == 0 { KINS } else { kin };
+
+        Signature {
+            kin,
+            seal: (kin - 1) % SEALS + 1,
+            tone: (kin - 1) % TONES + 1,
+        }
+    }
+
+    // none for 29 february - 0.0 hunab ku is not counted
+    pub fn from_date(date: NaiveDate) -> Option<Signature> {
+        if is_hunab_ku(date) {
+            return None;
+        }
+
+        let days = day_count(date) - day_count(epoch());
+        let kin = (i64::from(EPOCH_KIN - 1) + days).rem_euclid(i64::from(KINS)) + 1;
+
+        Self::new(kin as i32)
+    }
+}
+
+pub fn is_hunab_ku(date: NaiveDate) -> bool {
+    date.month() == 2 && date.day() == 29
+}
+
+fn epoch() -> NaiveDate {
+    NaiveDate::from_ymd(1987, 7, 26)
+}
+
+pub fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+// days since 1 ce with all 29 februaries thrown out
+pub fn day_count(date: NaiveDate) -> i64 {
+    let year = i64::from(date.year());
+    let previous = year - 1;
+    let mut leap_days =
+        previous.div_euclid(4) - previous.div_euclid(100) + previous.div_euclid(400);
+    if is_leap_year(year) && date.month() > 2 {
+        leap_days += 1;
+    }
+
+    i64::from(date.num_days_from_ce()) - leap_days
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kin(year: i32, month: u32, day: u32) -> Option<i32> {
+        Signature::from_date(NaiveDate::from_ymd(year, month, day)).map(|s| s.kin)
+    }
+
+    #[test]
+    fn reference_dates() {
+        let table = [
+            ((1939, 1, 24), 11),
+            ((1987, 7, 25), 33),
+            ((1987, 7, 26), 34),
+            ((2012, 12, 21), 207),
+            ((2013, 7, 26), 164),
+            ((2014, 7, 26), 9),
+            ((2015, 7, 26), 114),
+            ((2016, 7, 26), 219),
+            ((2017, 7, 26), 64),
+            ((2018, 7, 26), 169),
+            ((2019, 7, 26), 14),
+            ((2020, 7, 25), 118),
+            ((2020, 7, 26), 119),
+            ((2021, 7, 26), 224),
+        ];
+
+        for &((year, month, day), expected) in table.iter() {
+            assert_eq!(
+                kin(year, month, day),
+                Some(expected),
+                "{}-{}-{}",
+                year,
+                month,
+                day
+            );
+        }
+    }
+
+    #[test]
+    fn hunab_ku_is_not_counted() {
+        assert_eq!(kin(2020, 2, 29), None);
+        assert_eq!(kin(2000, 2, 29), None);
+        assert_eq!(kin(2020, 2, 28).map(|k| k % KINS + 1), kin(2020, 3, 1));
+        assert_eq!(kin(2000, 2, 28).map(|k| k % KINS + 1), kin(2000, 3, 1));
+        // 1900 is not a leap year
+        assert_eq!(kin(1900, 2, 28).map(|k| k % KINS + 1), kin(1900, 3, 1));
+    }
+
+    #[test]
+    fn seal_and_tone() {
+        let signature = Signature::new(207).unwrap();
+        assert_eq!(signature.seal, 7);
+        assert_eq!(signature.tone, 12);
+
+        let signature = Signature::new(260).unwrap();
+        assert_eq!(signature.seal, 20);
+        assert_eq!(signature.tone, 13);
+
+        for kin in 1..=KINS {
+            let signature = Signature::new(kin).unwrap();
+            assert_eq!(
+                Signature::from_seal_and_tone(signature.seal, signature.tone),
+                signature
+            );
+        }
+
+        assert_eq!(Signature::new(0), None);
+        assert_eq!(Signature::new(261), None);
+    }
+}
@@ -0,0 +1,56 @@
+use super::signature::Signature;
+use super::TONES;
+
+// 13 kins from magnetic to cosmic tone
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Wavespell {
+    // 1-20 in the tzolkin
+    pub number: i32,
+    // wavespell is named by its magnetic seal
+    pub seal: i32,
+    // position of the requested kin, same as its tone
+    pub position: i32,
+    pub kins: Vec<Signature>,
+}
+
+impl Wavespell {
+    pub fn new(signature: Signature) -> Wavespell {
+        let first = signature.kin - signature.tone + 1;
+        let kins: Vec<Signature> = (first..first + TONES).filter_map(Signature::new).collect();
+
+        Wavespell {
+            number: (first - 1) / TONES + 1,
+            seal: kins[0].seal,
+            position: signature.tone,
+            kins,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::KINS;
+
+    #[test]
+    fn wavespells() {
+        for kin in 1..=KINS {
+            let signature = Signature::new(kin).unwrap();
+            let wavespell = Wavespell::new(signature);
+
+            assert_eq!(wavespell.kins.len(), 13);
+            assert_eq!(wavespell.kins[0].tone, 1);
+            assert_eq!(wavespell.kins[12].tone, 13);
+            assert_eq!(wavespell.kins[wavespell.position as usize - 1], signature);
+        }
+    }
+
+    #[test]
+    fn known_wavespell() {
+        // kin 207 is in the 16th wavespell - yellow warrior
+        let wavespell = Wavespell::new(Signature::new(207).unwrap());
+        assert_eq!(wavespell.number, 16);
+        assert_eq!(wavespell.seal, 16);
+        assert_eq!(wavespell.kins[0].kin, 196);
+    }
+}
@@ -0,0 +1,71 @@
+use super::moon::year_start;
+use super::signature::{is_hunab_ku, Signature};
+use chrono::{Datelike, NaiveDate};
+
+// kin of the first day of 13-moon year,
+// always one of seed, moon, wizard or storm, none for years out of range
+pub fn year_bearer(year: i32) -> Option<Signature> {
+    // 26 july is never 0.0 hunab ku
+    Signature::from_date(year_start(year)?)
+}
+
+// kin of the birthday inside the 13-moon year, none for years out of range
+// and for 29 february birthdays - 0.0 hunab ku has no kin, see Signature::from_date
+pub fn personal_year(birthday: NaiveDate, year: i32) -> Option<Signature> {
+    if is_hunab_ku(birthday) {
+        return None;
+    }
+
+    let (month, day) = (birthday.month(), birthday.day());
+    let gregorian_year = if (month, day) >= (7, 26) {
+        year
+    } else {
+        year.checked_add(1)?
+    };
+
+    Signature::from_date(NaiveDate::from_ymd_opt(gregorian_year, month, day)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn year_bearers() {
+        assert_eq!(year_bearer(1987).unwrap().kin, 34);
+        assert_eq!(year_bearer(2013).unwrap().kin, 164);
+        assert_eq!(year_bearer(2014).unwrap().kin, 9);
+
+        for year in 1900..2100 {
+            let bearer = year_bearer(year).unwrap();
+            assert!([4, 9, 14, 19].contains(&bearer.seal));
+            assert_eq!(
+                (year_bearer(year + 1).unwrap().kin - bearer.kin + 260) % 260,
+                105
+            );
+        }
+    }
+
+    #[test]
+    fn out_of_range_years() {
+        assert_eq!(year_bearer(300_000), None);
+        let birthday = NaiveDate::from_ymd(2012, 12, 21);
+        assert_eq!(personal_year(birthday, 300_000), None);
+        assert_eq!(personal_year(birthday, i32::MAX), None);
+    }
+
+    #[test]
+    fn personal_years() {
+        let birthday = NaiveDate::from_ymd(2012, 12, 21);
+        assert_eq!(personal_year(birthday, 2012).unwrap().kin, 207);
+        assert_eq!(personal_year(birthday, 2013).unwrap().kin, 52);
+
+        let birthday = NaiveDate::from_ymd(1988, 7, 26);
+        assert_eq!(personal_year(birthday, 2013).unwrap().kin, 164);
+
+        // same as the kin of the birthday itself
+        let leap = NaiveDate::from_ymd(2000, 2, 29);
+        assert_eq!(Signature::from_date(leap), None);
+        assert_eq!(personal_year(leap, 2019), None);
+    }
+}
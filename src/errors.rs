@@ -1,7 +1,11 @@
+use crate::auth::LOGIN_TAKEN;
+use crate::models::form::FieldErrors;
+use bcrypt::BcryptError;
 use diesel::result::Error as DieselError;
 use rocket::http::Status;
 use rocket::request::Request;
-use rocket::response::Responder;
+use rocket::response::{Responder, Response};
+use rocket_contrib::json::Json;
 use std::convert::From;
 use std::{error, fmt};
 
@@ -11,6 +15,9 @@ pub enum DreamError {
     InternalServerError,
     BadRequest,
     Unauthorized,
+    Forbidden,
+    // field -> message, for forms and api clients
+    Invalid(FieldErrors),
 }
 
 impl fmt::Display for DreamError {
@@ -20,6 +27,8 @@ impl fmt::Display for DreamError {
             DreamError::InternalServerError => write!(f, "InternalServerError"),
             DreamError::BadRequest => write!(f, "BadRequest"),
             DreamError::Unauthorized => write!(f, "Unauthorized"),
+            DreamError::Forbidden => write!(f, "Forbidden"),
+            DreamError::Invalid(ref errors) => write!(f, "Invalid: {:?}", errors),
         }
     }
 }
@@ -31,6 +40,8 @@ impl error::Error for DreamError {
             DreamError::InternalServerError => "Internal server error",
             DreamError::BadRequest => "Bad Request",
             DreamError::Unauthorized => "Unauthorized",
+            DreamError::Forbidden => "Forbidden",
+            DreamError::Invalid(_) => "Invalid data",
         }
     }
 }
@@ -39,18 +50,107 @@ impl From<DieselError> for DreamError {
     fn from(error: DieselError) -> Self {
         match error {
             DieselError::NotFound => DreamError::NotFound,
+            // unique and check constraints are shown as form errors
+            DieselError::DatabaseError(_, ref info) => match info.constraint_name() {
+                Some("users_name_key") => DreamError::Invalid(field_error("login", LOGIN_TAKEN)),
+                Some(name) if name.ends_with("_num_key") => {
+                    DreamError::Invalid(field_error("num", "Запись с таким номером уже есть"))
+                }
+                Some(name) if name.ends_with("_num_check") => {
+                    DreamError::Invalid(field_error("num", "Номер вне допустимого диапазона"))
+                }
+                _ => {
+                    println!("Database error: {}", error);
+                    DreamError::InternalServerError
+                }
+            },
             _ => DreamError::InternalServerError,
         }
     }
 }
 
+fn field_error(field: &'static str, message: &'static str) -> FieldErrors {
+    let mut errors = FieldErrors::new();
+    errors.insert(field, message);
+
+    errors
+}
+
+impl From<BcryptError> for DreamError {
+    fn from(error: BcryptError) -> Self {
+        println!("Password hashing error: {}", error);
+        DreamError::InternalServerError
+    }
+}
+
 impl<'r> Responder<'r> for DreamError {
-    fn respond_to(self, _: &Request) -> rocket::response::Result<'r> {
+    fn respond_to(self, request: &Request) -> rocket::response::Result<'r> {
         match self {
+            DreamError::Invalid(errors) => Response::build_from(Json(errors).respond_to(request)?)
+                .status(Status::UnprocessableEntity)
+                .ok(),
             DreamError::NotFound => Err(Status::NotFound),
             DreamError::BadRequest => Err(Status::BadRequest),
             DreamError::Unauthorized => Err(Status::Unauthorized),
+            DreamError::Forbidden => Err(Status::Forbidden),
             _ => Err(Status::InternalServerError),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diesel::result::{DatabaseErrorInformation, DatabaseErrorKind};
+
+    struct Violation(&'static str);
+
+    impl DatabaseErrorInformation for Violation {
+        fn message(&self) -> &str {
+            "violation"
+        }
+        fn details(&self) -> Option<&str> {
+            None
+        }
+        fn hint(&self) -> Option<&str> {
+            None
+        }
+        fn table_name(&self) -> Option<&str> {
+            None
+        }
+        fn column_name(&self) -> Option<&str> {
+            None
+        }
+        fn constraint_name(&self) -> Option<&str> {
+            Some(self.0)
+        }
+    }
+
+    fn invalid(constraint: &'static str) -> Option<FieldErrors> {
+        let error = DieselError::DatabaseError(
+            DatabaseErrorKind::UniqueViolation,
+            Box::new(Violation(constraint)),
+        );
+        match DreamError::from(error) {
+            DreamError::Invalid(errors) => Some(errors),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn constraints() {
+        assert_eq!(
+            invalid("users_name_key"),
+            Some(field_error("login", LOGIN_TAKEN))
+        );
+        assert_eq!(
+            invalid("kins_num_key"),
+            Some(field_error("num", "Запись с таким номером уже есть"))
+        );
+        assert_eq!(
+            invalid("glyphs_num_check"),
+            Some(field_error("num", "Номер вне допустимого диапазона"))
+        );
+        assert_eq!(invalid("api_tokens_token_key"), None);
+    }
+}
@@ -8,17 +8,26 @@ extern crate rocket_contrib;
 extern crate diesel;
 #[macro_use]
 extern crate serde_derive;
+extern crate bcrypt;
 extern crate chrono;
+extern crate getrandom;
+extern crate ring;
 extern crate rocket_multipart_form_data;
+extern crate zip;
 
 use diesel::PgConnection;
 use rocket::Rocket;
 use rocket_contrib::{serve::StaticFiles, templates::Template};
-use views::{admin, pages};
+use views::{admin, api, pages, profiles};
 
+mod accounts;
 mod auth;
+mod bundle;
+mod calendar;
 mod errors;
 mod models;
+mod seed;
+mod timezone;
 mod views;
 
 type DreamResult<T> = Result<T, errors::DreamError>;
@@ -30,18 +39,73 @@ fn rocket() -> Rocket {
     rocket::ignite()
         .attach(Db::fairing())
         .attach(Template::fairing())
+        .attach(timezone::fairing())
         .mount("/static", StaticFiles::from("static/"))
         .mount(
             "/",
             routes![
                 pages::index,
+                pages::calculate,
+                pages::today,
+                pages::set_timezone,
+                pages::kin,
+                pages::oracle,
+                pages::wavespell,
+                pages::wavespell_by_date,
+                pages::moon,
+                pages::tzolkin,
+                pages::relationship,
                 pages::login_page,
                 pages::login,
                 pages::logout,
-                pages::registration_page
+                pages::registration_page,
+                pages::registration,
             ],
         )
-        .mount("/admin", routes![admin::main])
+        .mount("/api", routes![api::today])
+        .mount("/api/v1", routes![api::calc])
+        .mount(
+            "/api/v1/glyphs",
+            routes![
+                api::glyphs::list,
+                api::glyphs::get,
+                api::glyphs::create,
+                api::glyphs::update,
+                api::glyphs::delete,
+            ],
+        )
+        .mount(
+            "/api/v1/tones",
+            routes![
+                api::tones::list,
+                api::tones::get,
+                api::tones::create,
+                api::tones::update,
+                api::tones::delete,
+            ],
+        )
+        .mount(
+            "/api/v1/kins",
+            routes![
+                api::kins::list,
+                api::kins::get,
+                api::kins::create,
+                api::kins::update,
+                api::kins::delete,
+            ],
+        )
+        .mount(
+            "/profiles",
+            routes![
+                profiles::list,
+                profiles::add,
+                profiles::create,
+                profiles::edit,
+                profiles::update,
+                profiles::delete,
+            ],
+        )
+        .mount("/admin", routes![admin::main, admin::integrity])
         .mount(
             "/admin/glyphs",
             routes![
@@ -75,10 +139,50 @@ fn rocket() -> Rocket {
                 admin::kins::delete,
             ],
         )
-        .register(catchers![pages::not_found, pages::unauthorized])
+        .mount(
+            "/admin/users",
+            routes![admin::users::list, admin::users::update],
+        )
+        .mount(
+            "/admin/bundle",
+            routes![
+                admin::bundle::page,
+                admin::bundle::export,
+                admin::bundle::import
+            ],
+        )
+        .mount(
+            "/admin/tokens",
+            routes![
+                admin::tokens::list,
+                admin::tokens::create,
+                admin::tokens::delete,
+            ],
+        )
+        .register(catchers![
+            pages::bad_request,
+            pages::not_found,
+            pages::unauthorized,
+            pages::forbidden,
+            pages::unprocessable
+        ])
 }
 
 fn main() {
-    let error = rocket().launch();
-    println!("Launch failed: {}", error);
+    match std::env::args().nth(1).as_deref() {
+        // only the database is needed for commands
+        Some("seed") => seed::run(rocket::ignite().attach(Db::fairing())),
+        Some("create-admin") => {
+            let args: Vec<String> = std::env::args().skip(2).collect();
+            accounts::run(rocket::ignite().attach(Db::fairing()), &args)
+        }
+        Some(command @ "export") | Some(command @ "import") => {
+            let args: Vec<String> = std::env::args().skip(2).collect();
+            bundle::run(rocket::ignite().attach(Db::fairing()), command, &args)
+        }
+        _ => {
+            let error = rocket().launch();
+            println!("Launch failed: {}", error);
+        }
+    }
 }
@@ -0,0 +1,173 @@
+use super::form::MultipartModel;
+use super::schema::{glyphs, kins, tones};
+use super::utils::delete_file;
+use diesel::prelude::*;
+
+// rows of a content table (glyphs, tones, kins): the queries come from
+// content_table!, updates and deletes that touch images are shared here
+pub trait Content: Sized {
+    type New: MultipartModel + Clone;
+
+    fn all(connection: &PgConnection) -> QueryResult<Vec<Self>>;
+
+    // one page ordered by num, pages start from 0
+    fn page(connection: &PgConnection, page: i64, per_page: i64) -> QueryResult<Vec<Self>>;
+
+    fn count(connection: &PgConnection) -> QueryResult<i64>;
+
+    fn nums(connection: &PgConnection) -> QueryResult<Vec<i32>>;
+
+    fn get(connection: &PgConnection, id: i32) -> QueryResult<Self>;
+
+    fn by_num(connection: &PgConnection, num: i32) -> QueryResult<Self>;
+
+    fn insert(connection: &PgConnection, new_item: Self::New) -> QueryResult<Self>;
+
+    // rows with already taken numbers are skipped
+    fn insert_missing(connection: &PgConnection, new_items: Vec<Self::New>) -> QueryResult<usize>;
+
+    // plain update of the row, images are not looked at
+    fn set(connection: &PgConnection, id: i32, new_item: Self::New) -> QueryResult<Self>;
+
+    // plain delete of the row, images are not looked at
+    fn delete_row(connection: &PgConnection, id: i32) -> QueryResult<Self>;
+
+    fn id(&self) -> i32;
+
+    fn image(&self) -> &str;
+
+    // update without touching files, returns the old row as well
+    fn update_row(
+        connection: &PgConnection,
+        mut new_item: Self::New,
+        id: i32,
+    ) -> QueryResult<(Self, Self)> {
+        let old_item = Self::get(connection, id)?;
+        if new_item.image().is_empty() {
+            // keep old image name in case of update without image
+            new_item.set_image(old_item.image().to_string());
+        }
+        let item = Self::set(connection, id, new_item)?;
+
+        Ok((old_item, item))
+    }
+
+    fn update(connection: &PgConnection, new_item: Self::New, id: i32) -> QueryResult<Self> {
+        let (old_item, item) = Self::update_row(connection, new_item, id)?;
+        // old image is removed only after successful update
+        if item.image() != old_item.image() {
+            delete_unused_image(connection, old_item.image())?;
+        }
+
+        Ok(item)
+    }
+
+    fn delete(connection: &PgConnection, id: i32) -> QueryResult<Self> {
+        let item = Self::delete_row(connection, id)?;
+        // remove related image once the row is gone
+        delete_unused_image(connection, item.image())?;
+
+        Ok(item)
+    }
+}
+
+// the api and bundles take images by name, so several rows
+// may share one file, it goes only with the last of them
+pub fn delete_unused_image(connection: &PgConnection, image: &str) -> QueryResult<()> {
+    if image.is_empty() || image_in_use(connection, image)? {
+        return Ok(());
+    }
+    delete_file(image);
+
+    Ok(())
+}
+
+pub fn image_in_use(connection: &PgConnection, image: &str) -> QueryResult<bool> {
+    let glyphs: i64 = glyphs::table
+        .filter(glyphs::image.eq(image))
+        .count()
+        .get_result(connection)?;
+    let tones: i64 = tones::table
+        .filter(tones::image.eq(image))
+        .count()
+        .get_result(connection)?;
+    let kins: i64 = kins::table
+        .filter(kins::image.eq(image))
+        .count()
+        .get_result(connection)?;
+
+    Ok(glyphs + tones + kins > 0)
+}
+
+// Content for a model with id, num and image columns,
+// e.g. content_table!(Glyph, NewGlyph, glyphs)
+macro_rules! content_table {
+    ($t:ident, $nt:ident, $table:ident) => {
+        impl crate::models::content::Content for $t {
+            type New = $nt;
+
+            fn all(connection: &PgConnection) -> QueryResult<Vec<$t>> {
+                $table::table.order($table::id.asc()).load(connection)
+            }
+
+            fn page(connection: &PgConnection, page: i64, per_page: i64) -> QueryResult<Vec<$t>> {
+                $table::table
+                    .order(($table::num.asc(), $table::id.asc()))
+                    .limit(per_page)
+                    .offset(page * per_page)
+                    .load(connection)
+            }
+
+            fn count(connection: &PgConnection) -> QueryResult<i64> {
+                $table::table.count().get_result(connection)
+            }
+
+            fn nums(connection: &PgConnection) -> QueryResult<Vec<i32>> {
+                $table::table.select($table::num).load(connection)
+            }
+
+            fn get(connection: &PgConnection, id: i32) -> QueryResult<$t> {
+                $table::table.find(id).get_result(connection)
+            }
+
+            fn by_num(connection: &PgConnection, num: i32) -> QueryResult<$t> {
+                $table::table.filter($table::num.eq(num)).first(connection)
+            }
+
+            fn insert(connection: &PgConnection, new_item: $nt) -> QueryResult<$t> {
+                diesel::insert_into($table::table)
+                    .values(new_item)
+                    .get_result(connection)
+            }
+
+            fn insert_missing(
+                connection: &PgConnection,
+                new_items: Vec<$nt>,
+            ) -> QueryResult<usize> {
+                diesel::insert_into($table::table)
+                    .values(&new_items)
+                    .on_conflict($table::num)
+                    .do_nothing()
+                    .execute(connection)
+            }
+
+            fn set(connection: &PgConnection, id: i32, new_item: $nt) -> QueryResult<$t> {
+                diesel::update($table::table.find(id))
+                    .set(new_item)
+                    .get_result(connection)
+            }
+
+            fn delete_row(connection: &PgConnection, id: i32) -> QueryResult<$t> {
+                diesel::delete($table::table.find(id)).get_result(connection)
+            }
+
+            fn id(&self) -> i32 {
+                self.id
+            }
+
+            fn image(&self) -> &str {
+                &self.image
+            }
+        }
+    };
+}
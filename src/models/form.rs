@@ -0,0 +1,292 @@
+use super::utils::{file_exists, file_name_with_prefix, is_plain_name, save_file};
+use crate::errors::DreamError;
+use crate::timezone::Timezone;
+use rocket::data::{FromDataSimple, Outcome};
+use rocket::http::Status;
+use rocket::{Data, Outcome::*, Request};
+use rocket_multipart_form_data::{
+    mime, FileField, MultipartFormData, MultipartFormDataError, MultipartFormDataField,
+    MultipartFormDataOptions, SingleFileField, TextField,
+};
+use std::collections::BTreeMap;
+
+// field name -> message shown under the input
+pub type FieldErrors = BTreeMap<&'static str, &'static str>;
+
+// bigger images are rejected with a field error while parsing
+pub const IMAGE_SIZE_LIMIT: u64 = 2 * 1024 * 1024;
+const IMAGE_TOO_LARGE: &str = "Изображение должно быть не больше 2 МБ";
+
+// submitted item with its validation errors,
+// rendered back into the form when something is wrong
+#[derive(Serialize)]
+pub struct Submission<T> {
+    pub item: T,
+    pub errors: FieldErrors,
+    // parsing stopped at a too big image, the item holds no submitted values
+    pub partial: bool,
+}
+
+impl<T> Submission<T> {
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+// content edited through a multipart form with an optional "image" file,
+// implementing it is enough for the admin and api handlers
+pub trait MultipartModel: Sized + Default {
+    // text fields of the form
+    const FIELDS: &'static [&'static str];
+
+    // build the item from the form, the image is set later
+    fn from_form(form: &mut MultipartForm) -> Self;
+
+    // checks that don't depend on parsing
+    fn validate(&self) -> FieldErrors;
+
+    fn num(&self) -> i32;
+
+    fn image(&self) -> &str;
+
+    fn set_image(&mut self, image: String);
+}
+
+impl<T: MultipartModel> FromDataSimple for Submission<T> {
+    type Error = DreamError;
+
+    fn from_data(request: &Request, data: Data) -> Outcome<Self, Self::Error> {
+        let mut form = match MultipartForm::parse(request, data, T::FIELDS) {
+            Ok(form) => form,
+            // parsing stops at a too big image, the rest of the form is lost
+            Err(DreamError::Invalid(errors)) => {
+                return Success(Submission {
+                    item: T::default(),
+                    errors,
+                    partial: true,
+                })
+            }
+            Err(error) => return Failure((Status::BadRequest, error)),
+        };
+
+        let mut item = T::from_form(&mut form);
+        let MultipartForm { data, mut errors } = form;
+        merge(&mut errors, item.validate());
+
+        // image is saved only for a valid form
+        let image = image(&data, &mut errors);
+        if let (Some(file), true) = (image, errors.is_empty()) {
+            item.set_image(save_image(request, file));
+        }
+
+        Success(Submission {
+            item,
+            errors,
+            partial: false,
+        })
+    }
+}
+
+// parsed form with the errors found so far
+pub struct MultipartForm {
+    data: MultipartFormData,
+    errors: FieldErrors,
+}
+
+impl MultipartForm {
+    fn parse(request: &Request, data: Data, texts: &[&str]) -> Result<MultipartForm, DreamError> {
+        let mut options = MultipartFormDataOptions::new();
+        options
+            .allowed_fields
+            .push(MultipartFormDataField::file("image").size_limit(IMAGE_SIZE_LIMIT));
+        for field in texts {
+            options
+                .allowed_fields
+                .push(MultipartFormDataField::text(field));
+        }
+
+        // check if the content type is set properly
+        let content_type = request.content_type().ok_or(DreamError::BadRequest)?;
+
+        match MultipartFormData::parse(content_type, data, options) {
+            Ok(data) => Ok(MultipartForm {
+                data,
+                errors: FieldErrors::new(),
+            }),
+            Err(MultipartFormDataError::DataTooLargeError(ref field)) if &**field == "image" => {
+                let mut errors = FieldErrors::new();
+                errors.insert("image", IMAGE_TOO_LARGE);
+                Err(DreamError::Invalid(errors))
+            }
+            Err(error) => {
+                println!("Multipart form parsing error: {:?}", error);
+                Err(DreamError::BadRequest)
+            }
+        }
+    }
+
+    // missing field is an empty string
+    pub fn text(&self, field: &str) -> String {
+        match self.data.texts.get(field) {
+            Some(TextField::Single(text)) => text.text.clone(),
+            _ => String::new(),
+        }
+    }
+
+    // blank or broken number is reported and replaced by 0
+    pub fn number(&mut self, field: &'static str) -> i32 {
+        match self.text(field).trim().parse() {
+            Ok(number) => number,
+            Err(_) => {
+                self.errors.insert(field, "Введите число");
+                0
+            }
+        }
+    }
+
+    // checkbox is sent only when checked
+    pub fn checkbox(&self, field: &str) -> bool {
+        self.data.texts.get(field).is_some()
+    }
+}
+
+// uploaded jpg or png, none if the file input was left empty
+fn image<'a>(form: &'a MultipartFormData, errors: &mut FieldErrors) -> Option<&'a SingleFileField> {
+    let file = match form.files.get("image") {
+        Some(FileField::Single(file)) => file,
+        _ => return None,
+    };
+    let file_name = match &file.file_name {
+        Some(file_name) if !file_name.is_empty() => file_name.to_lowercase(),
+        _ => return None,
+    };
+
+    let is_image = match &file.content_type {
+        Some(content_type) => *content_type == mime::IMAGE_PNG || *content_type == mime::IMAGE_JPEG,
+        None => false,
+    };
+    if !is_image || !has_image_extension(&file_name) {
+        errors.insert("image", "Изображение должно быть в формате jpg или png");
+        return None;
+    }
+
+    Some(file)
+}
+
+// jpg or png by the file name
+pub fn has_image_extension(file_name: &str) -> bool {
+    let file_name = file_name.to_lowercase();
+    [".png", ".jpg", ".jpeg"]
+        .iter()
+        .any(|extension| file_name.ends_with(extension))
+}
+
+// image given by name (json api, bundles) must be already uploaded,
+// empty name keeps the old image
+pub fn image_name_error(image: &str) -> Option<&'static str> {
+    if image.is_empty() || file_exists(image) {
+        None
+    } else if !is_plain_name(image) {
+        Some("Недопустимое имя файла")
+    } else {
+        Some("Изображение не найдено")
+    }
+}
+
+// copy uploaded image to static/upload, returns the new file name
+fn save_image(request: &Request, file: &SingleFileField) -> String {
+    let today = request
+        .guard::<Timezone>()
+        .succeeded()
+        .unwrap_or_default()
+        .today();
+    // browsers may send a full path
+    let file_name = file
+        .file_name
+        .as_ref()
+        .and_then(|name| std::path::Path::new(name).file_name())
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+    let image = file_name_with_prefix(today, file_name);
+    save_file(&file.path, &image);
+
+    image
+}
+
+// model errors never override the parsing ones
+fn merge(errors: &mut FieldErrors, other: FieldErrors) {
+    for (field, message) in other {
+        errors.entry(field).or_insert(message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::glyph::NewGlyph;
+    use rocket::http::ContentType;
+    use rocket::local::Client;
+
+    #[post("/", data = "<submission>")]
+    fn submit(submission: Submission<NewGlyph>) -> String {
+        format!(
+            "{} {:?} {}",
+            submission.item.name, submission.errors, submission.partial
+        )
+    }
+
+    // multipart body with a name and an image of the given size
+    fn upload(size: usize) -> String {
+        let mut body = b"--X\r\nContent-Disposition: form-data; name=\"num\"\r\n\r\n1\r\n".to_vec();
+        body.extend_from_slice(
+            b"--X\r\nContent-Disposition: form-data; name=\"name\"\r\n\r\nDragon\r\n",
+        );
+        body.extend_from_slice(
+            b"--X\r\nContent-Disposition: form-data; name=\"image\"; filename=\"\"\r\n",
+        );
+        body.extend_from_slice(b"Content-Type: image/png\r\n\r\n");
+        body.extend(std::iter::repeat(b'x').take(size));
+        body.extend_from_slice(b"\r\n--X--\r\n");
+
+        let client = Client::new(rocket::ignite().mount("/", routes![submit])).unwrap();
+        let mut response = client
+            .post("/")
+            .header(ContentType::with_params(
+                "multipart",
+                "form-data",
+                ("boundary", "X"),
+            ))
+            .body(body)
+            .dispatch();
+
+        response.body_string().unwrap_or_default()
+    }
+
+    #[test]
+    fn image_size() {
+        // empty file name means no new image
+        assert_eq!(upload(1024), "Dragon {} false");
+        assert_eq!(
+            upload(3 * 1024 * 1024),
+            format!(" {{\"image\": \"{}\"}} true", IMAGE_TOO_LARGE)
+        );
+        assert_eq!(
+            upload(9 * 1024 * 1024),
+            format!(" {{\"image\": \"{}\"}} true", IMAGE_TOO_LARGE)
+        );
+    }
+
+    #[test]
+    fn image_names() {
+        assert_eq!(image_name_error(""), None);
+        assert_eq!(
+            image_name_error("../../victim.txt"),
+            Some("Недопустимое имя файла")
+        );
+        assert_eq!(image_name_error(".hidden"), Some("Недопустимое имя файла"));
+        assert_eq!(
+            image_name_error("never_uploaded.png"),
+            Some("Изображение не найдено")
+        );
+    }
+}
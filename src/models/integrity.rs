@@ -0,0 +1,120 @@
+use super::content::Content;
+use super::glyph::Glyph;
+use super::kin::Kin;
+use super::tone::Tone;
+use crate::calendar::classification::is_portal;
+use crate::calendar::{KINS, SEALS, TONES};
+use diesel::prelude::*;
+
+// numbers of one content table compared with 1..=expected
+#[derive(Serialize)]
+pub struct TableReport {
+    pub title: &'static str,
+    pub expected: i32,
+    pub count: usize,
+    pub missing: Vec<i32>,
+    pub duplicates: Vec<i32>,
+    pub out_of_range: Vec<i32>,
+}
+
+impl TableReport {
+    pub fn new(title: &'static str, expected: i32, mut nums: Vec<i32>) -> TableReport {
+        nums.sort();
+        let count = nums.len();
+        let missing = (1..=expected)
+            .filter(|num| nums.binary_search(num).is_err())
+            .collect();
+        let out_of_range = nums
+            .iter()
+            .cloned()
+            .filter(|num| *num < 1 || *num > expected)
+            .collect();
+        let mut duplicates: Vec<i32> = nums
+            .windows(2)
+            .filter(|pair| pair[0] == pair[1])
+            .map(|pair| pair[0])
+            .collect();
+        duplicates.dedup();
+
+        TableReport {
+            title,
+            expected,
+            count,
+            missing,
+            duplicates,
+            out_of_range,
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty() && self.duplicates.is_empty() && self.out_of_range.is_empty()
+    }
+}
+
+// what is missing or broken in glyphs, tones and kins
+#[derive(Serialize)]
+pub struct IntegrityReport {
+    pub tables: Vec<TableReport>,
+    // kins with portal flag different from the loom of maya
+    pub wrong_portals: Vec<i32>,
+    pub complete: bool,
+}
+
+impl IntegrityReport {
+    pub fn load(connection: &PgConnection) -> QueryResult<IntegrityReport> {
+        let tables = vec![
+            TableReport::new("Печати", SEALS, Glyph::nums(connection)?),
+            TableReport::new("Тона", TONES, Tone::nums(connection)?),
+            TableReport::new("Кины", KINS, Kin::nums(connection)?),
+        ];
+        let mut wrong_portals: Vec<i32> = Kin::all(connection)?
+            .into_iter()
+            .filter(|kin| kin.portal != is_portal(kin.num))
+            .map(|kin| kin.num)
+            .collect();
+        wrong_portals.sort();
+        let complete = wrong_portals.is_empty() && tables.iter().all(TableReport::is_complete);
+
+        Ok(IntegrityReport {
+            tables,
+            wrong_portals,
+            complete,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn complete_table() {
+        let report = TableReport::new("Тона", 13, (1..=13).rev().collect());
+        assert_eq!(report.count, 13);
+        assert!(report.missing.is_empty());
+        assert!(report.is_complete());
+    }
+
+    #[test]
+    fn empty_table() {
+        let report = TableReport::new("Печати", 20, vec![]);
+        assert_eq!(report.count, 0);
+        assert_eq!(report.missing, (1..=20).collect::<Vec<_>>());
+        assert!(!report.is_complete());
+    }
+
+    #[test]
+    fn broken_table() {
+        let report = TableReport::new("Тона", 13, vec![13, 0, 2, 2, 5, 14, 2, 1, 14, -3]);
+        assert_eq!(report.count, 10);
+        assert_eq!(report.missing, vec![3, 4, 6, 7, 8, 9, 10, 11, 12]);
+        assert_eq!(report.duplicates, vec![2, 14]);
+        assert_eq!(report.out_of_range, vec![-3, 0, 14, 14]);
+        assert!(!report.is_complete());
+
+        let report = TableReport::new("Тона", 13, (0..=13).collect());
+        assert!(report.missing.is_empty());
+        assert_eq!(report.out_of_range, vec![0]);
+        assert!(!report.is_complete());
+    }
+}
@@ -1,6 +1,13 @@
+#[macro_use]
+pub mod content;
+pub mod form;
 pub mod glyph;
+pub mod integrity;
 pub mod kin;
+pub mod profile;
+pub mod reading;
 pub mod schema;
+pub mod token;
 pub mod tone;
 pub mod user;
 pub mod utils;
@@ -0,0 +1,64 @@
+use super::schema::profiles;
+use chrono::{NaiveDate, NaiveDateTime};
+use diesel::prelude::*;
+
+#[derive(Insertable, AsChangeset)]
+#[table_name = "profiles"]
+pub struct NewProfile {
+    pub user_id: i32,
+    pub name: String,
+    pub birthday: NaiveDate,
+    pub notes: String,
+}
+
+// saved birthday of a family member or client
+#[derive(Serialize, Queryable, Identifiable, Debug)]
+pub struct Profile {
+    pub id: i32,
+    pub user_id: i32,
+    pub name: String,
+    pub birthday: NaiveDate,
+    pub notes: String,
+    pub created_at: NaiveDateTime,
+}
+
+// every query is scoped by the owner
+impl Profile {
+    pub fn all(connection: &PgConnection, user_id: i32) -> QueryResult<Vec<Profile>> {
+        profiles::table
+            .filter(profiles::user_id.eq(user_id))
+            .order(profiles::name.asc())
+            .load(connection)
+    }
+
+    pub fn get(connection: &PgConnection, user_id: i32, id: i32) -> QueryResult<Profile> {
+        profiles::table
+            .filter(profiles::user_id.eq(user_id))
+            .find(id)
+            .get_result(connection)
+    }
+
+    pub fn insert(connection: &PgConnection, new_profile: NewProfile) -> QueryResult<Profile> {
+        diesel::insert_into(profiles::table)
+            .values(new_profile)
+            .get_result(connection)
+    }
+
+    pub fn update(
+        connection: &PgConnection,
+        new_profile: NewProfile,
+        id: i32,
+    ) -> QueryResult<Profile> {
+        let old_profile = Self::get(connection, new_profile.user_id, id)?;
+
+        diesel::update(&old_profile)
+            .set(new_profile)
+            .get_result(connection)
+    }
+
+    pub fn delete(connection: &PgConnection, user_id: i32, id: i32) -> QueryResult<Profile> {
+        let profile = Self::get(connection, user_id, id)?;
+
+        diesel::delete(&profile).get_result(connection)
+    }
+}
@@ -0,0 +1,291 @@
+use super::content::Content;
+use super::glyph::Glyph;
+use super::kin::Kin;
+use super::profile::Profile;
+use super::tone::Tone;
+use crate::calendar::classification::{is_portal, Classification};
+use crate::calendar::moon::{year_of, MoonDate};
+use crate::calendar::oracle::Oracle;
+use crate::calendar::relationship::Relationship;
+use crate::calendar::signature::{is_hunab_ku, Signature};
+use crate::calendar::wavespell::Wavespell;
+use crate::calendar::year::{personal_year, year_bearer};
+use crate::calendar::{KINS, SEALS};
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use std::collections::HashMap;
+
+// calculated signature with related content,
+// content is optional - admin could not fill it yet
+#[derive(Serialize)]
+pub struct Reading {
+    pub signature: Signature,
+    pub classification: Classification,
+    pub kin: Option<Kin>,
+    pub glyph: Option<Glyph>,
+    pub tone: Option<Tone>,
+}
+
+impl Reading {
+    pub fn load(connection: &PgConnection, signature: Signature) -> QueryResult<Reading> {
+        let kin = Kin::by_num(connection, signature.kin).optional()?;
+        let mut classification = Classification::new(signature);
+        // stored portal flag wins, computed one is for missing kins
+        if let Some(kin) = &kin {
+            classification.portal = kin.portal;
+        }
+
+        Ok(Reading {
+            signature,
+            classification,
+            kin,
+            glyph: Glyph::by_num(connection, signature.seal).optional()?,
+            tone: Tone::by_num(connection, signature.tone).optional()?,
+        })
+    }
+}
+
+// readings for the oracle partners of a kin
+#[derive(Serialize)]
+pub struct OracleReading {
+    pub guide: Reading,
+    pub analog: Reading,
+    pub antipode: Reading,
+    pub occult: Reading,
+}
+
+impl OracleReading {
+    pub fn load(connection: &PgConnection, oracle: Oracle) -> QueryResult<OracleReading> {
+        Ok(OracleReading {
+            guide: Reading::load(connection, oracle.guide)?,
+            analog: Reading::load(connection, oracle.analog)?,
+            antipode: Reading::load(connection, oracle.antipode)?,
+            occult: Reading::load(connection, oracle.occult)?,
+        })
+    }
+}
+
+// day of a wavespell with its kin and tone content
+#[derive(Serialize)]
+pub struct WavespellDay {
+    pub signature: Signature,
+    pub kin: Option<Kin>,
+    pub tone: Option<Tone>,
+    pub portal: bool,
+}
+
+#[derive(Serialize)]
+pub struct WavespellReading {
+    pub wavespell: Wavespell,
+    // magnetic seal of the wavespell
+    pub glyph: Option<Glyph>,
+    pub days: Vec<WavespellDay>,
+}
+
+impl WavespellReading {
+    pub fn load(connection: &PgConnection, wavespell: Wavespell) -> QueryResult<WavespellReading> {
+        let nums: Vec<i32> = wavespell.kins.iter().map(|s| s.kin).collect();
+        let mut kins = Kin::by_nums(connection, &nums)?;
+        let mut tones = Tone::all(connection)?;
+
+        let days = wavespell
+            .kins
+            .iter()
+            .map(|&signature| {
+                let kin = take_by(&mut kins, |k| k.num == signature.kin);
+
+                WavespellDay {
+                    signature,
+                    portal: kin
+                        .as_ref()
+                        .map_or_else(|| is_portal(signature.kin), |k| k.portal),
+                    kin,
+                    tone: take_by(&mut tones, |t| t.num == signature.tone),
+                }
+            })
+            .collect();
+
+        Ok(WavespellReading {
+            glyph: Glyph::by_num(connection, wavespell.seal).optional()?,
+            wavespell,
+            days,
+        })
+    }
+}
+
+fn take_by<T, F: Fn(&T) -> bool>(items: &mut Vec<T>, predicate: F) -> Option<T> {
+    items
+        .iter()
+        .position(predicate)
+        .map(|index| items.remove(index))
+}
+
+// year bearer and personal year for the birthday
+#[derive(Serialize)]
+pub struct YearReading {
+    pub year: i32,
+    pub bearer: Reading,
+    // none for 0.0 hunab ku
+    pub personal: Option<Reading>,
+}
+
+impl YearReading {
+    pub fn load(
+        connection: &PgConnection,
+        birthday: NaiveDate,
+        year: i32,
+    ) -> QueryResult<Option<YearReading>> {
+        // none for years out of range
+        let bearer = match year_bearer(year) {
+            Some(bearer) => bearer,
+            None => return Ok(None),
+        };
+        let personal = match personal_year(birthday, year) {
+            Some(personal) => Some(Reading::load(connection, personal)?),
+            None if is_hunab_ku(birthday) => None,
+            None => return Ok(None),
+        };
+
+        Ok(Some(YearReading {
+            year,
+            bearer: Reading::load(connection, bearer)?,
+            personal,
+        }))
+    }
+}
+
+#[derive(Serialize)]
+pub struct TzolkinCell {
+    pub signature: Signature,
+    pub kin: Option<Kin>,
+    pub portal: bool,
+    pub selected: bool,
+}
+
+// harmonic module: 20 rows of seals, 13 columns of 20 kins
+#[derive(Serialize)]
+pub struct Tzolkin {
+    pub selected: Option<Signature>,
+    pub rows: Vec<Vec<TzolkinCell>>,
+}
+
+impl Tzolkin {
+    pub fn load(connection: &PgConnection, selected: Option<Signature>) -> QueryResult<Tzolkin> {
+        let mut kins: HashMap<i32, Kin> = Kin::all(connection)?
+            .into_iter()
+            .map(|kin| (kin.num, kin))
+            .collect();
+
+        let rows = (1..=SEALS)
+            .map(|seal| {
+                (0..KINS / SEALS)
+                    .filter_map(|column| Signature::new(column * SEALS + seal))
+                    .map(|signature| {
+                        let kin = kins.remove(&signature.kin);
+
+                        TzolkinCell {
+                            signature,
+                            portal: kin
+                                .as_ref()
+                                .map_or_else(|| is_portal(signature.kin), |k| k.portal),
+                            kin,
+                            selected: selected == Some(signature),
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Ok(Tzolkin { selected, rows })
+    }
+}
+
+// saved profile with its kin, oracle and 13-moon birthday
+#[derive(Serialize)]
+pub struct ProfileReading {
+    pub profile: Profile,
+    pub moon_date: MoonDate,
+    // none for 0.0 hunab ku
+    pub reading: Option<Reading>,
+    pub oracle: Option<Oracle>,
+}
+
+impl ProfileReading {
+    pub fn load(connection: &PgConnection, profile: Profile) -> QueryResult<ProfileReading> {
+        let signature = Signature::from_date(profile.birthday);
+
+        Ok(ProfileReading {
+            moon_date: MoonDate::from_date(profile.birthday),
+            reading: match signature {
+                Some(signature) => Some(Reading::load(connection, signature)?),
+                None => None,
+            },
+            oracle: signature.map(Oracle::new),
+            profile,
+        })
+    }
+}
+
+#[derive(Serialize)]
+pub struct RelationshipReading {
+    pub relationship: Relationship,
+    pub first: Reading,
+    pub second: Reading,
+    pub composite: Reading,
+}
+
+impl RelationshipReading {
+    pub fn load(
+        connection: &PgConnection,
+        relationship: Relationship,
+    ) -> QueryResult<RelationshipReading> {
+        Ok(RelationshipReading {
+            first: Reading::load(connection, relationship.first)?,
+            second: Reading::load(connection, relationship.second)?,
+            composite: Reading::load(connection, relationship.composite)?,
+            relationship,
+        })
+    }
+}
+
+// everything about a single day
+#[derive(Serialize)]
+pub struct DayReading {
+    pub date: NaiveDate,
+    pub moon_date: MoonDate,
+    // none for 0.0 hunab ku
+    pub reading: Option<Reading>,
+    pub oracle: Option<OracleReading>,
+    pub wavespell: Option<WavespellReading>,
+    // 13-moon year of the day
+    pub year: i32,
+    // none only for the earliest dates chrono knows
+    pub year_bearer: Option<Reading>,
+}
+
+impl DayReading {
+    pub fn load(connection: &PgConnection, date: NaiveDate) -> QueryResult<DayReading> {
+        let mut day = DayReading {
+            date,
+            moon_date: MoonDate::from_date(date),
+            reading: None,
+            oracle: None,
+            wavespell: None,
+            year: year_of(date),
+            year_bearer: year_bearer(year_of(date))
+                .map(|signature| Reading::load(connection, signature))
+                .transpose()?,
+        };
+
+        if let Some(signature) = Signature::from_date(date) {
+            day.reading = Some(Reading::load(connection, signature)?);
+            day.oracle = Some(OracleReading::load(connection, Oracle::new(signature))?);
+            day.wavespell = Some(WavespellReading::load(
+                connection,
+                Wavespell::new(signature),
+            )?);
+        }
+
+        Ok(day)
+    }
+}
@@ -1,3 +1,13 @@
+table! {
+    api_tokens (id) {
+        id -> Int4,
+        user_id -> Int4,
+        name -> Varchar,
+        token_hash -> Varchar,
+        created_at -> Timestamp,
+    }
+}
+
 table! {
     glyphs (id) {
         id -> Int4,
@@ -15,6 +25,18 @@ table! {
         num -> Int4,
         name -> Varchar,
         image -> Varchar,
+        portal -> Bool,
+    }
+}
+
+table! {
+    profiles (id) {
+        id -> Int4,
+        user_id -> Int4,
+        name -> Varchar,
+        birthday -> Date,
+        notes -> Text,
+        created_at -> Timestamp,
     }
 }
 
@@ -35,12 +57,18 @@ table! {
         name -> Varchar,
         password -> Varchar,
         created_at -> Timestamp,
+        role -> Varchar,
     }
 }
 
+joinable!(api_tokens -> users (user_id));
+joinable!(profiles -> users (user_id));
+
 allow_tables_to_appear_in_same_query!(
+    api_tokens,
     glyphs,
     kins,
+    profiles,
     tones,
     users,
 );
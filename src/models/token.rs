@@ -0,0 +1,109 @@
+use super::schema::api_tokens;
+use super::user::User;
+use crate::errors::DreamError;
+use crate::DreamResult;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use ring::digest::{digest, SHA256};
+
+#[derive(Insertable)]
+#[table_name = "api_tokens"]
+pub struct NewApiToken {
+    pub user_id: i32,
+    pub name: String,
+    pub token_hash: String,
+}
+
+// bearer token for scripts, acts on behalf of its user
+#[derive(Serialize, Queryable, Identifiable, Debug)]
+pub struct ApiToken {
+    pub id: i32,
+    pub user_id: i32,
+    pub name: String,
+    #[serde(skip)]
+    pub token_hash: String,
+    pub created_at: NaiveDateTime,
+}
+
+impl ApiToken {
+    pub fn all(connection: &PgConnection, user_id: i32) -> QueryResult<Vec<ApiToken>> {
+        api_tokens::table
+            .filter(api_tokens::user_id.eq(user_id))
+            .order(api_tokens::id.asc())
+            .load(connection)
+    }
+
+    // only the hash is stored, the token is returned to be shown once
+    pub fn insert(
+        connection: &PgConnection,
+        user_id: i32,
+        name: &str,
+    ) -> DreamResult<(ApiToken, String)> {
+        let token = generate()?;
+        let new_token = NewApiToken {
+            user_id,
+            name: name.to_string(),
+            token_hash: hash(&token),
+        };
+        let api_token = diesel::insert_into(api_tokens::table)
+            .values(new_token)
+            .get_result(connection)?;
+
+        Ok((api_token, token))
+    }
+
+    pub fn delete(connection: &PgConnection, user_id: i32, id: i32) -> QueryResult<ApiToken> {
+        let token: ApiToken = api_tokens::table
+            .filter(api_tokens::user_id.eq(user_id))
+            .find(id)
+            .get_result(connection)?;
+
+        diesel::delete(&token).get_result(connection)
+    }
+
+    // owner of the token
+    pub fn user(connection: &PgConnection, token: &str) -> QueryResult<User> {
+        let api_token: ApiToken = api_tokens::table
+            .filter(api_tokens::token_hash.eq(hash(token)))
+            .first(connection)?;
+
+        User::get(connection, api_token.user_id)
+    }
+}
+
+// 32 random bytes as hex
+fn generate() -> DreamResult<String> {
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).map_err(|error| {
+        println!("Token generation error: {}", error);
+        DreamError::InternalServerError
+    })?;
+
+    Ok(to_hex(&bytes))
+}
+
+// tokens are long and random, a plain sha-256 is enough to look them up
+fn hash(token: &str) -> String {
+    to_hex(digest(&SHA256, token.as_bytes()).as_ref())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashes() {
+        assert_eq!(
+            hash("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        let token = generate().unwrap();
+        assert_eq!(token.len(), 64);
+        assert_ne!(hash(&token), token);
+        assert_ne!(generate().unwrap(), token);
+    }
+}
@@ -0,0 +1,126 @@
+use super::schema::users;
+use crate::errors::DreamError;
+use crate::DreamResult;
+use bcrypt::{hash, verify, DEFAULT_COST};
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use rocket::http::RawStr;
+use rocket::request::FromFormValue;
+use std::str::FromStr;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    // everything including user management
+    Admin,
+    // content: glyphs, tones, kins
+    Editor,
+    // registered user
+    Member,
+}
+
+impl Role {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Role::Admin => "admin",
+            Role::Editor => "editor",
+            Role::Member => "member",
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = DreamError;
+
+    fn from_str(role: &str) -> Result<Role, DreamError> {
+        match role {
+            "admin" => Ok(Role::Admin),
+            "editor" => Ok(Role::Editor),
+            "member" => Ok(Role::Member),
+            _ => Err(DreamError::BadRequest),
+        }
+    }
+}
+
+impl<'v> FromFormValue<'v> for Role {
+    type Error = &'v RawStr;
+
+    fn from_form_value(form_value: &'v RawStr) -> Result<Role, &'v RawStr> {
+        form_value.as_str().parse().map_err(|_| form_value)
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "users"]
+pub struct NewUser {
+    pub name: String,
+    pub password: String,
+}
+
+#[derive(Serialize, Queryable, Identifiable, Debug)]
+pub struct User {
+    pub id: i32,
+    // login, email for registered users
+    pub name: String,
+    // bcrypt hash, never rendered
+    #[serde(skip_serializing)]
+    pub password: String,
+    pub created_at: NaiveDateTime,
+    // see Role, checked by the database
+    pub role: String,
+}
+
+impl User {
+    pub fn role(&self) -> Role {
+        self.role.parse().unwrap_or(Role::Member)
+    }
+
+    pub fn all(connection: &PgConnection) -> QueryResult<Vec<User>> {
+        users::table.order(users::id.asc()).load(connection)
+    }
+
+    pub fn get(connection: &PgConnection, id: i32) -> QueryResult<User> {
+        users::table.find(id).get_result(connection)
+    }
+
+    pub fn by_name(connection: &PgConnection, name: &str) -> QueryResult<User> {
+        users::table.filter(users::name.eq(name)).first(connection)
+    }
+
+    pub fn insert(connection: &PgConnection, name: &str, password: &str) -> DreamResult<User> {
+        let new_user = NewUser {
+            name: name.to_string(),
+            password: hash(password, DEFAULT_COST)?,
+        };
+
+        Ok(diesel::insert_into(users::table)
+            .values(new_user)
+            .get_result(connection)?)
+    }
+
+    pub fn set_role(connection: &PgConnection, id: i32, role: Role) -> QueryResult<User> {
+        diesel::update(users::table.find(id))
+            .set(users::role.eq(role.as_str()))
+            .get_result(connection)
+    }
+
+    // none for unknown user or wrong password,
+    // a stored password that is not a bcrypt hash never matches
+    pub fn login(
+        connection: &PgConnection,
+        name: &str,
+        password: &str,
+    ) -> DreamResult<Option<User>> {
+        match User::by_name(connection, name.trim()).optional()? {
+            Some(user) => match verify(password, &user.password) {
+                Ok(true) => Ok(Some(user)),
+                Ok(false) => Ok(None),
+                Err(error) => {
+                    println!("Login error for {}: {}", user.name, error);
+                    Ok(None)
+                }
+            },
+            None => Ok(None),
+        }
+    }
+}
@@ -1,22 +1,103 @@
-use chrono::{Datelike, Utc};
+use chrono::{Datelike, NaiveDate};
 
 // build "unique" filename with current date prefix
-pub fn file_name_with_prefix(file_path: &String) -> String {
-    let now = Utc::now();
-    let (_, year) = now.year_ce();
-    format!("{}_{}_{}_{}", year, now.month(), now.day(), file_path)
+pub fn file_name_with_prefix(today: NaiveDate, file_path: &str) -> String {
+    let (_, year) = today.year_ce();
+    format!("{}_{}_{}_{}", year, today.month(), today.day(), file_path)
+}
+
+// plain file name like "2020_4_1_hand.png": no folders, no "..", not hidden,
+// anything else would reach outside static/upload
+pub fn is_plain_name(file_name: &str) -> bool {
+    !file_name.is_empty()
+        && !file_name.starts_with('.')
+        && !file_name.contains('\\')
+        && std::path::Path::new(file_name)
+            .file_name()
+            .and_then(|name| name.to_str())
+            == Some(file_name)
 }
 
 // copy file from /tmp to static/upload with new filename
-pub fn save_file(path: &std::path::PathBuf, file_name: &String) {
+pub fn save_file(path: &std::path::PathBuf, file_name: &str) {
+    if !is_plain_name(file_name) {
+        println!("File error: {} is not a plain file name", file_name);
+        return;
+    }
     if let Err(error) = std::fs::copy(path, format!("static/upload/{}", file_name)) {
         println!("File error: {}", error);
     }
 }
 
 // delete file from static/upload
-pub fn delete_file(file_name: &String) {
+pub fn delete_file(file_name: &str) {
+    if !is_plain_name(file_name) {
+        println!("File error: {} is not a plain file name", file_name);
+        return;
+    }
     if let Err(error) = std::fs::remove_file(format!("static/upload/{}", file_name)) {
         println!("File error: {}", error);
     }
 }
+
+// public url of an uploaded file
+pub fn file_url(file_name: &str) -> Option<String> {
+    if is_plain_name(file_name) {
+        Some(format!("/static/upload/{}", file_name))
+    } else {
+        None
+    }
+}
+
+// content of a file from static/upload
+pub fn read_file(file_name: &str) -> Option<Vec<u8>> {
+    if !is_plain_name(file_name) {
+        return None;
+    }
+
+    std::fs::read(format!("static/upload/{}", file_name)).ok()
+}
+
+// write file to static/upload, the folder is created if needed
+pub fn write_file(file_name: &str, bytes: &[u8]) -> std::io::Result<()> {
+    if !is_plain_name(file_name) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("{} is not a plain file name", file_name),
+        ));
+    }
+    std::fs::create_dir_all("static/upload")?;
+    std::fs::write(format!("static/upload/{}", file_name), bytes)
+}
+
+pub fn file_exists(file_name: &str) -> bool {
+    is_plain_name(file_name)
+        && std::path::Path::new(&format!("static/upload/{}", file_name)).is_file()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_names() {
+        assert!(is_plain_name("hand.png"));
+        assert!(is_plain_name("2020_4_1_hand.png"));
+        assert!(!is_plain_name(""));
+        assert!(!is_plain_name(".."));
+        assert!(!is_plain_name(".hidden"));
+        assert!(!is_plain_name("../../victim.txt"));
+        assert!(!is_plain_name("images/hand.png"));
+        assert!(!is_plain_name("/etc/passwd"));
+        assert!(!is_plain_name("..\\victim.txt"));
+        assert!(!is_plain_name("hand.png/"));
+    }
+
+    #[test]
+    fn refuses_paths() {
+        assert_eq!(file_url("../../victim.txt"), None);
+        assert_eq!(read_file("../Cargo.toml"), None);
+        assert!(!file_exists("../Cargo.toml"));
+        assert!(write_file("../victim.txt", b"x").is_err());
+    }
+}
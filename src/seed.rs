@@ -0,0 +1,425 @@
+use crate::calendar::classification::is_portal;
+use crate::calendar::signature::Signature;
+use crate::calendar::{KINS, SEALS, TONES};
+use crate::models::content::Content;
+use crate::models::glyph::{Glyph, NewGlyph};
+use crate::models::kin::{Kin, NewKin};
+use crate::models::tone::{NewTone, Tone};
+use crate::Db;
+use diesel::PgConnection;
+use rocket::Rocket;
+
+#[derive(Clone, Copy)]
+enum Gender {
+    Masculine,
+    Feminine,
+    Neuter,
+}
+
+// russian adjective in three genders
+type Adjective = (&'static str, &'static str, &'static str);
+
+struct SealData {
+    name: &'static str,
+    gender: Gender,
+    keywords: &'static str,
+    english: &'static str,
+    english_keywords: &'static str,
+}
+
+struct ToneData {
+    name: Adjective,
+    keywords: &'static str,
+    english: &'static str,
+    english_keywords: &'static str,
+}
+
+// red, white, blue, yellow - repeats every 4 seals
+const COLORS: [Adjective; 4] = [
+    ("Красный", "Красная", "Красное"),
+    ("Белый", "Белая", "Белое"),
+    ("Синий", "Синяя", "Синее"),
+    ("Жёлтый", "Жёлтая", "Жёлтое"),
+];
+const ENGLISH_COLORS: [&str; 4] = ["Red", "White", "Blue", "Yellow"];
+
+// power, action and essence of every seal
+const SEAL_DATA: [SealData; 20] = [
+    SealData {
+        name: "Дракон",
+        gender: Gender::Masculine,
+        keywords: "Рождение · Питание · Бытие",
+        english: "Dragon",
+        english_keywords: "Birth · Nurtures · Being",
+    },
+    SealData {
+        name: "Ветер",
+        gender: Gender::Masculine,
+        keywords: "Дух · Общение · Дыхание",
+        english: "Wind",
+        english_keywords: "Spirit · Communicates · Breath",
+    },
+    SealData {
+        name: "Ночь",
+        gender: Gender::Feminine,
+        keywords: "Изобилие · Мечта · Интуиция",
+        english: "Night",
+        english_keywords: "Abundance · Dreams · Intuition",
+    },
+    SealData {
+        name: "Семя",
+        gender: Gender::Neuter,
+        keywords: "Цветение · Нацеленность · Осознанность",
+        english: "Seed",
+        english_keywords: "Flowering · Targets · Awareness",
+    },
+    SealData {
+        name: "Змей",
+        gender: Gender::Masculine,
+        keywords: "Жизненная сила · Выживание · Инстинкт",
+        english: "Serpent",
+        english_keywords: "Life Force · Survives · Instinct",
+    },
+    SealData {
+        name: "Соединитель Миров",
+        gender: Gender::Masculine,
+        keywords: "Смерть · Уравнивание · Возможность",
+        english: "Worldbridger",
+        english_keywords: "Death · Equalizes · Opportunity",
+    },
+    SealData {
+        name: "Рука",
+        gender: Gender::Feminine,
+        keywords: "Свершение · Знание · Исцеление",
+        english: "Hand",
+        english_keywords: "Accomplishment · Knows · Healing",
+    },
+    SealData {
+        name: "Звезда",
+        gender: Gender::Feminine,
+        keywords: "Элегантность · Украшение · Искусство",
+        english: "Star",
+        english_keywords: "Elegance · Beautifies · Art",
+    },
+    SealData {
+        name: "Луна",
+        gender: Gender::Feminine,
+        keywords: "Вселенская вода · Очищение · Поток",
+        english: "Moon",
+        english_keywords: "Universal Water · Purifies · Flow",
+    },
+    SealData {
+        name: "Собака",
+        gender: Gender::Feminine,
+        keywords: "Сердце · Любовь · Преданность",
+        english: "Dog",
+        english_keywords: "Heart · Loves · Loyalty",
+    },
+    SealData {
+        name: "Обезьяна",
+        gender: Gender::Feminine,
+        keywords: "Магия · Игра · Иллюзия",
+        english: "Monkey",
+        english_keywords: "Magic · Plays · Illusion",
+    },
+    SealData {
+        name: "Человек",
+        gender: Gender::Masculine,
+        keywords: "Свободная воля · Влияние · Мудрость",
+        english: "Human",
+        english_keywords: "Free Will · Influences · Wisdom",
+    },
+    SealData {
+        name: "Странник Небес",
+        gender: Gender::Masculine,
+        keywords: "Пространство · Исследование · Бодрствование",
+        english: "Skywalker",
+        english_keywords: "Space · Explores · Wakefulness",
+    },
+    SealData {
+        name: "Маг",
+        gender: Gender::Masculine,
+        keywords: "Безвременность · Очарование · Восприимчивость",
+        english: "Wizard",
+        english_keywords: "Timelessness · Enchants · Receptivity",
+    },
+    SealData {
+        name: "Орёл",
+        gender: Gender::Masculine,
+        keywords: "Видение · Созидание · Ум",
+        english: "Eagle",
+        english_keywords: "Vision · Creates · Mind",
+    },
+    SealData {
+        name: "Воин",
+        gender: Gender::Masculine,
+        keywords: "Разум · Вопрошание · Бесстрашие",
+        english: "Warrior",
+        english_keywords: "Intelligence · Questions · Fearlessness",
+    },
+    SealData {
+        name: "Земля",
+        gender: Gender::Feminine,
+        keywords: "Навигация · Эволюция · Синхронность",
+        english: "Earth",
+        english_keywords: "Navigation · Evolves · Synchronicity",
+    },
+    SealData {
+        name: "Зеркало",
+        gender: Gender::Neuter,
+        keywords: "Бесконечность · Отражение · Порядок",
+        english: "Mirror",
+        english_keywords: "Endlessness · Reflects · Order",
+    },
+    SealData {
+        name: "Буря",
+        gender: Gender::Feminine,
+        keywords: "Самопорождение · Катализ · Энергия",
+        english: "Storm",
+        english_keywords: "Self-Generation · Catalyzes · Energy",
+    },
+    SealData {
+        name: "Солнце",
+        gender: Gender::Neuter,
+        keywords: "Вселенский огонь · Просветление · Жизнь",
+        english: "Sun",
+        english_keywords: "Universal Fire · Enlightens · Life",
+    },
+];
+
+// action, power and essence of every tone
+const TONE_DATA: [ToneData; 13] = [
+    ToneData {
+        name: ("Магнитный", "Магнитная", "Магнитное"),
+        keywords: "Объединение · Притяжение · Цель",
+        english: "Magnetic",
+        english_keywords: "Unify · Attract · Purpose",
+    },
+    ToneData {
+        name: ("Лунный", "Лунная", "Лунное"),
+        keywords: "Поляризация · Стабилизация · Вызов",
+        english: "Lunar",
+        english_keywords: "Polarize · Stabilize · Challenge",
+    },
+    ToneData {
+        name: ("Электрический", "Электрическая", "Электрическое"),
+        keywords: "Активация · Связывание · Служение",
+        english: "Electric",
+        english_keywords: "Activate · Bond · Service",
+    },
+    ToneData {
+        name: ("Самосущный", "Самосущная", "Самосущное"),
+        keywords: "Определение · Измерение · Форма",
+        english: "Self-Existing",
+        english_keywords: "Define · Measure · Form",
+    },
+    ToneData {
+        name: ("Обертонный", "Обертонная", "Обертонное"),
+        keywords: "Наделение силой · Командование · Сияние",
+        english: "Overtone",
+        english_keywords: "Empower · Command · Radiance",
+    },
+    ToneData {
+        name: ("Ритмический", "Ритмическая", "Ритмическое"),
+        keywords: "Организация · Уравновешивание · Равенство",
+        english: "Rhythmic",
+        english_keywords: "Organize · Balance · Equality",
+    },
+    ToneData {
+        name: ("Резонансный", "Резонансная", "Резонансное"),
+        keywords: "Направление · Вдохновение · Настройка",
+        english: "Resonant",
+        english_keywords: "Channel · Inspire · Attunement",
+    },
+    ToneData {
+        name: ("Галактический", "Галактическая", "Галактическое"),
+        keywords: "Гармонизация · Моделирование · Целостность",
+        english: "Galactic",
+        english_keywords: "Harmonize · Model · Integrity",
+    },
+    ToneData {
+        name: ("Солнечный", "Солнечная", "Солнечное"),
+        keywords: "Пульсация · Реализация · Намерение",
+        english: "Solar",
+        english_keywords: "Pulse · Realize · Intention",
+    },
+    ToneData {
+        name: ("Планетарный", "Планетарная", "Планетарное"),
+        keywords: "Совершенствование · Производство · Проявление",
+        english: "Planetary",
+        english_keywords: "Perfect · Produce · Manifestation",
+    },
+    ToneData {
+        name: ("Спектральный", "Спектральная", "Спектральное"),
+        keywords: "Растворение · Высвобождение · Освобождение",
+        english: "Spectral",
+        english_keywords: "Dissolve · Release · Liberation",
+    },
+    ToneData {
+        name: ("Кристальный", "Кристальная", "Кристальное"),
+        keywords: "Посвящение · Универсализация · Сотрудничество",
+        english: "Crystal",
+        english_keywords: "Dedicate · Universalize · Cooperation",
+    },
+    ToneData {
+        name: ("Космический", "Космическая", "Космическое"),
+        keywords: "Выдержка · Превосхождение · Присутствие",
+        english: "Cosmic",
+        english_keywords: "Endure · Transcend · Presence",
+    },
+];
+
+fn agree(adjective: Adjective, gender: Gender) -> &'static str {
+    match gender {
+        Gender::Masculine => adjective.0,
+        Gender::Feminine => adjective.1,
+        Gender::Neuter => adjective.2,
+    }
+}
+
+// key words in russian with english below
+fn preview(keywords: &str, english: &str, english_keywords: &str) -> String {
+    format!(
+        "<p>{}</p><p><i>{}: {}</i></p>",
+        keywords, english, english_keywords
+    )
+}
+
+pub fn glyphs() -> Vec<NewGlyph> {
+    (1..=SEALS)
+        .map(|num| {
+            let seal = &SEAL_DATA[(num - 1) as usize];
+            let color = ((num - 1) % 4) as usize;
+            let english = format!("{} {}", ENGLISH_COLORS[color], seal.english);
+
+            NewGlyph {
+                num,
+                name: format!("{} {}", agree(COLORS[color], seal.gender), seal.name),
+                image: String::new(),
+                preview: preview(seal.keywords, &english, seal.english_keywords),
+                description: String::new(),
+            }
+        })
+        .collect()
+}
+
+pub fn tones() -> Vec<NewTone> {
+    (1..=TONES)
+        .map(|num| {
+            let tone = &TONE_DATA[(num - 1) as usize];
+
+            NewTone {
+                num,
+                name: tone.name.0.to_string(),
+                image: String::new(),
+                preview: preview(tone.keywords, tone.english, tone.english_keywords),
+                description: String::new(),
+            }
+        })
+        .collect()
+}
+
+// color, tone and seal in both languages:
+// "Синяя Кристальная Рука (Blue Crystal Hand)"
+pub fn kins() -> Vec<NewKin> {
+    (1..=KINS)
+        .filter_map(Signature::new)
+        .map(|signature| {
+            let seal = &SEAL_DATA[(signature.seal - 1) as usize];
+            let tone = &TONE_DATA[(signature.tone - 1) as usize];
+            let color = ((signature.seal - 1) % 4) as usize;
+
+            NewKin {
+                num: signature.kin,
+                name: format!(
+                    "{} {} {} ({} {} {})",
+                    agree(COLORS[color], seal.gender),
+                    agree(tone.name, seal.gender),
+                    seal.name,
+                    ENGLISH_COLORS[color],
+                    tone.english,
+                    seal.english
+                ),
+                image: String::new(),
+                portal: is_portal(signature.kin),
+            }
+        })
+        .collect()
+}
+
+// insert missing glyphs, tones and kins, existing rows are kept as is
+pub fn seed(connection: &PgConnection) -> diesel::QueryResult<(usize, usize, usize)> {
+    Ok((
+        Glyph::insert_missing(connection, glyphs())?,
+        Tone::insert_missing(connection, tones())?,
+        Kin::insert_missing(connection, kins())?,
+    ))
+}
+
+// `dreamspell seed`
+pub fn run(rocket: Rocket) {
+    let connection = match Db::get_one(&rocket) {
+        Some(connection) => connection,
+        None => {
+            println!("Seed failed: no database connection");
+            std::process::exit(1);
+        }
+    };
+
+    match seed(&connection) {
+        Ok((glyphs, tones, kins)) => println!(
+            "Seeded glyphs: {}, tones: {}, kins: {}",
+            glyphs, tones, kins
+        ),
+        Err(error) => {
+            println!("Seed failed: {}", error);
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn unique(nums: Vec<i32>) -> usize {
+        nums.into_iter().collect::<HashSet<_>>().len()
+    }
+
+    #[test]
+    fn counts() {
+        assert_eq!(glyphs().len(), 20);
+        assert_eq!(tones().len(), 13);
+        assert_eq!(kins().len(), 260);
+
+        assert_eq!(unique(glyphs().iter().map(|glyph| glyph.num).collect()), 20);
+        assert_eq!(unique(tones().iter().map(|tone| tone.num).collect()), 13);
+        assert_eq!(unique(kins().iter().map(|kin| kin.num).collect()), 260);
+        assert_eq!(kins().iter().filter(|kin| kin.portal).count(), 52);
+    }
+
+    #[test]
+    fn names() {
+        let glyphs = glyphs();
+        assert_eq!(glyphs[0].name, "Красный Дракон");
+        assert_eq!(glyphs[3].name, "Жёлтое Семя");
+        assert!(glyphs[6].preview.contains("Blue Hand"));
+
+        let tones = tones();
+        assert_eq!(tones[11].name, "Кристальный");
+        assert!(tones[11].preview.contains("Crystal"));
+
+        let kins = kins();
+        assert_eq!(
+            kins[0].name,
+            "Красный Магнитный Дракон (Red Magnetic Dragon)"
+        );
+        assert_eq!(kins[206].name, "Синяя Кристальная Рука (Blue Crystal Hand)");
+        assert_eq!(
+            kins[259].name,
+            "Жёлтое Космическое Солнце (Yellow Cosmic Sun)"
+        );
+        assert!(kins.iter().all(|kin| kin.image.is_empty()));
+    }
+}
@@ -0,0 +1,172 @@
+use chrono::{FixedOffset, NaiveDate, Utc};
+use rocket::fairing::AdHoc;
+use rocket::request::{self, FromRequest, Request};
+use rocket::{Outcome, State};
+
+// offset from rocket config, e.g. timezone = "+06:00"
+pub struct DefaultTimezone(FixedOffset);
+
+// offset of the current request: ?tz=, "tz" cookie or the default one
+#[derive(Clone, Copy, Debug)]
+pub struct Timezone(pub FixedOffset);
+
+impl Timezone {
+    pub fn today(self) -> NaiveDate {
+        Utc::now().with_timezone(&self.0).date().naive_local()
+    }
+
+    pub fn name(self) -> String {
+        self.0.to_string()
+    }
+}
+
+impl Default for Timezone {
+    fn default() -> Timezone {
+        Timezone(FixedOffset::east(0))
+    }
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for Timezone {
+    type Error = !;
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Timezone, !> {
+        let query = request
+            .get_query_value::<String>("tz")
+            .and_then(|value| value.ok());
+        let cookie = request
+            .cookies()
+            .get("tz")
+            .map(|cookie| cookie.value().to_string());
+        let default = match request.guard::<State<DefaultTimezone>>() {
+            Outcome::Success(timezone) => timezone.0,
+            _ => Timezone::default().0,
+        };
+
+        // broken values fall through to the next source
+        let offset = query
+            .and_then(|value| parse_offset(&value))
+            .or_else(|| cookie.and_then(|value| parse_offset(&value)))
+            .unwrap_or(default);
+
+        Outcome::Success(Timezone(offset))
+    }
+}
+
+// read the default timezone on launch, invalid config stops the server
+pub fn fairing() -> AdHoc {
+    AdHoc::on_attach("Timezone", |rocket| {
+        let value = rocket
+            .config()
+            .get_str("timezone")
+            .unwrap_or("+00:00")
+            .to_string();
+
+        match parse_offset(&value) {
+            Some(offset) => Ok(rocket.manage(DefaultTimezone(offset))),
+            None => {
+                println!("Invalid timezone in config: {}", value);
+                Err(rocket)
+            }
+        }
+    })
+}
+
+// "UTC", "Z", "+6", "+06", "-03:30", "+0530"
+pub fn parse_offset(value: &str) -> Option<FixedOffset> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("utc") || value == "Z" {
+        return Some(FixedOffset::east(0));
+    }
+
+    let sign = match value.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    let value = &value[1..];
+    let (hours, minutes) = match value.find(':') {
+        Some(index) => (&value[..index], &value[index + 1..]),
+        None if value.len() == 4 && is_number(value) => (&value[..2], &value[2..]),
+        None => (value, "0"),
+    };
+    // parse() alone would let "+-5" or "+03:-30" through
+    if !is_number(hours) || !is_number(minutes) || hours.len() > 2 || minutes.len() > 2 {
+        return None;
+    }
+    let hours: i32 = hours.parse().ok()?;
+    let minutes: i32 = minutes.parse().ok()?;
+    if hours > 14 || minutes > 59 {
+        return None;
+    }
+
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+fn is_number(value: &str) -> bool {
+    !value.is_empty() && value.bytes().all(|byte| byte.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::config::{Config, Environment};
+    use rocket::http::Cookie;
+    use rocket::local::Client;
+
+    #[get("/")]
+    fn current(timezone: Timezone) -> String {
+        timezone.name()
+    }
+
+    #[test]
+    fn offsets() {
+        assert_eq!(parse_offset("UTC"), Some(FixedOffset::east(0)));
+        assert_eq!(parse_offset("+6"), Some(FixedOffset::east(6 * 3600)));
+        assert_eq!(parse_offset("+06:00"), Some(FixedOffset::east(6 * 3600)));
+        assert_eq!(
+            parse_offset("-03:30"),
+            Some(FixedOffset::west(3 * 3600 + 1800))
+        );
+        assert_eq!(
+            parse_offset("+0530"),
+            Some(FixedOffset::east(5 * 3600 + 1800))
+        );
+        assert_eq!(parse_offset("6"), None);
+        assert_eq!(parse_offset("+25"), None);
+        assert_eq!(parse_offset("+06:75"), None);
+        assert_eq!(parse_offset(""), None);
+        assert_eq!(parse_offset("+aéb"), None);
+        assert_eq!(parse_offset("+éé"), None);
+        assert_eq!(parse_offset("+-5"), None);
+        assert_eq!(parse_offset("+03:-30"), None);
+        assert_eq!(parse_offset("+03:"), None);
+        assert_eq!(parse_offset("+:30"), None);
+    }
+
+    #[test]
+    fn query_then_cookie_then_config() {
+        let config = Config::build(Environment::Development)
+            .extra("timezone", "+03:00")
+            .finalize()
+            .unwrap();
+        let rocket = rocket::custom(config)
+            .attach(fairing())
+            .mount("/", routes![current]);
+        let client = Client::new(rocket).unwrap();
+        let name = |uri: &str, cookie: Option<&str>| {
+            let mut request = client.get(uri.to_string());
+            if let Some(value) = cookie {
+                request = request.cookie(Cookie::new("tz", value.to_string()));
+            }
+            request.dispatch().body_string().unwrap()
+        };
+
+        assert_eq!(name("/", None), "+03:00");
+        assert_eq!(name("/", Some("+06:00")), "+06:00");
+        assert_eq!(name("/?tz=-05:00", Some("+06:00")), "-05:00");
+        assert_eq!(name("/?tz=-05:00", None), "-05:00");
+        // broken values fall through
+        assert_eq!(name("/?tz=%2Ba%C3%A9b", Some("+06:00")), "+06:00");
+        assert_eq!(name("/?tz=broken", Some("+aéb")), "+03:00");
+    }
+}
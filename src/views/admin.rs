@@ -1,13 +1,49 @@
+use crate::errors::DreamError;
+use crate::models::form::FieldErrors;
+use crate::models::integrity::IntegrityReport;
+use crate::models::utils::delete_file;
+use crate::DreamResult;
+use diesel::result::Error as DieselError;
+
 #[get("/")]
-pub fn main() -> rocket::response::Redirect {
+pub fn main(_editor: crate::auth::Editor) -> rocket::response::Redirect {
     rocket::response::Redirect::to("/admin/glyphs")
 }
 
+// missing, duplicated and out of range numbers
+#[get("/integrity")]
+pub fn integrity(
+    _editor: crate::auth::Editor,
+    connection: crate::Db,
+) -> DreamResult<rocket_contrib::templates::Template> {
+    let report = IntegrityReport::load(&connection)?;
+
+    Ok(rocket_contrib::templates::Template::render(
+        "admin/integrity",
+        report,
+    ))
+}
+
+// constraint violations go back to the form without the uploaded image
+pub fn rejected(error: DieselError, image: &str) -> DreamResult<FieldErrors> {
+    match DreamError::from(error) {
+        DreamError::Invalid(errors) => {
+            if !image.is_empty() {
+                delete_file(image);
+            }
+
+            Ok(errors)
+        }
+        error => Err(error),
+    }
+}
+
 macro_rules! handle {
     ($t:ty, $nt:ty, $tp:expr) => {
         // list of items
         #[get("/")]
         pub fn list(
+            _editor: crate::auth::Editor,
             connection: crate::Db,
         ) -> crate::DreamResult<rocket_contrib::templates::Template> {
             let items = <$t>::all(&connection)?;
@@ -22,68 +58,137 @@ macro_rules! handle {
 
         // show add form
         #[get("/add")]
-        pub fn add() -> rocket_contrib::templates::Template {
-            rocket_contrib::templates::Template::render(
-                format!("{}/add", $tp),
-                crate::views::NoContext {},
-            )
+        pub fn add(_editor: crate::auth::Editor) -> rocket_contrib::templates::Template {
+            let context = crate::views::FormContext::new(
+                None,
+                String::new(),
+                <$nt>::default(),
+                crate::models::form::FieldErrors::new(),
+            );
+
+            rocket_contrib::templates::Template::render(format!("{}/add", $tp), context)
         }
 
-        // create item
-        #[post("/", data = "<new_item>")]
+        // create item or show the form again with errors
+        #[post("/", data = "<submission>")]
         pub fn create(
+            _editor: crate::auth::Editor,
             connection: crate::Db,
-            new_item: crate::DreamResult<$nt>,
-        ) -> crate::DreamResult<rocket::response::Redirect> {
-            match new_item {
-                Ok(item) => {
-                    let _item = <$t>::insert(&connection, item)?;
-                    Ok(rocket::response::Redirect::to(format!("/{}", $tp)))
-                }
-                Err(error) => {
-                    println!("Error: {}", error);
-                    Ok(rocket::response::Redirect::to(format!("/{}/add", $tp)))
+            submission: crate::DreamResult<crate::models::form::Submission<$nt>>,
+        ) -> crate::DreamResult<
+            Result<rocket::response::Redirect, rocket_contrib::templates::Template>,
+        > {
+            let mut submission = submission?;
+            if submission.is_valid() {
+                match <$t>::insert(&connection, submission.item.clone()) {
+                    Ok(_item) => {
+                        return Ok(Ok(rocket::response::Redirect::to(format!("/{}", $tp))))
+                    }
+                    Err(error) => {
+                        submission.errors =
+                            crate::views::admin::rejected(error, &submission.item.image)?
+                    }
                 }
             }
+
+            let context = crate::views::FormContext::new(
+                None,
+                String::new(),
+                submission.item,
+                submission.errors,
+            );
+
+            Ok(Err(rocket_contrib::templates::Template::render(
+                format!("{}/add", $tp),
+                context,
+            )))
         }
 
         // show edit form
         #[get("/<id>")]
         pub fn edit(
+            _editor: crate::auth::Editor,
             connection: crate::Db,
             id: i32,
         ) -> crate::DreamResult<rocket_contrib::templates::Template> {
             let item = <$t>::get(&connection, id)?;
+            let context = crate::views::FormContext::new(
+                Some(id),
+                item.image.clone(),
+                item,
+                crate::models::form::FieldErrors::new(),
+            );
 
             Ok(rocket_contrib::templates::Template::render(
                 format!("{}/edit", $tp),
-                item,
+                context,
             ))
         }
 
-        // update item
+        // update item or show the form again with errors
         // post here instead of put - because of multipart
-        #[post("/<id>", data = "<new_item>")]
+        #[post("/<id>", data = "<submission>")]
         pub fn update(
+            _editor: crate::auth::Editor,
             connection: crate::Db,
-            new_item: crate::DreamResult<$nt>,
+            submission: crate::DreamResult<crate::models::form::Submission<$nt>>,
             id: i32,
-        ) -> crate::DreamResult<rocket::response::Redirect> {
-            match new_item {
-                Ok(item) => {
-                    let _item = <$t>::update(&connection, item, id)?;
-                }
-                Err(error) => {
-                    println!("Error: {}", error);
+        ) -> crate::DreamResult<
+            Result<rocket::response::Redirect, rocket_contrib::templates::Template>,
+        > {
+            let mut submission = submission?;
+            let old_item = <$t>::get(&connection, id)?;
+            if submission.is_valid() {
+                match <$t>::update(&connection, submission.item.clone(), id) {
+                    Ok(_item) => {
+                        return Ok(Ok(rocket::response::Redirect::to(format!(
+                            "/{}/{}",
+                            $tp, id
+                        ))))
+                    }
+                    Err(error) => {
+                        // keep the current image file
+                        let image = if submission.item.image == old_item.image {
+                            ""
+                        } else {
+                            &submission.item.image
+                        };
+                        submission.errors = crate::views::admin::rejected(error, image)?
+                    }
                 }
             }
 
-            Ok(rocket::response::Redirect::to(format!("/{}/{}", $tp, id)))
+            // nothing but the error came through, show the stored values
+            let template = format!("{}/edit", $tp);
+            if submission.partial {
+                let context = crate::views::FormContext::new(
+                    Some(id),
+                    old_item.image.clone(),
+                    old_item,
+                    submission.errors,
+                );
+
+                return Ok(Err(rocket_contrib::templates::Template::render(
+                    template, context,
+                )));
+            }
+
+            let context = crate::views::FormContext::new(
+                Some(id),
+                old_item.image,
+                submission.item,
+                submission.errors,
+            );
+
+            Ok(Err(rocket_contrib::templates::Template::render(
+                template, context,
+            )))
         }
 
         // delete item
         #[delete("/<id>")]
         pub fn delete(
+            _editor: crate::auth::Editor,
             connection: crate::Db,
             id: i32,
         ) -> crate::DreamResult<rocket::response::Redirect> {
@@ -95,16 +200,197 @@ macro_rules! handle {
 }
 
 pub mod glyphs {
+    use crate::models::content::Content;
     use crate::models::glyph::{Glyph, NewGlyph};
     handle!(Glyph, NewGlyph, "admin/glyphs");
 }
 
 pub mod tones {
+    use crate::models::content::Content;
     use crate::models::tone::{NewTone, Tone};
     handle!(Tone, NewTone, "admin/tones");
 }
 
 pub mod kins {
+    use crate::models::content::Content;
     use crate::models::kin::{Kin, NewKin};
     handle!(Kin, NewKin, "admin/kins");
 }
+
+// user roles, admins only
+pub mod users {
+    use crate::auth::{Admin, RoleForm};
+    use crate::models::user::User;
+    use crate::views::TemplateContext;
+    use crate::{Db, DreamResult};
+    use rocket::request::Form;
+    use rocket::response::Redirect;
+    use rocket_contrib::templates::Template;
+
+    #[get("/")]
+    pub fn list(_admin: Admin, connection: Db) -> DreamResult<Template> {
+        let items = User::all(&connection)?;
+        let context = TemplateContext { items };
+
+        Ok(Template::render("admin/users/list", context))
+    }
+
+    #[post("/<id>", data = "<role_form>")]
+    pub fn update(
+        _admin: Admin,
+        connection: Db,
+        id: i32,
+        role_form: Form<RoleForm>,
+    ) -> DreamResult<Redirect> {
+        let _user = User::set_role(&connection, id, role_form.role)?;
+
+        Ok(Redirect::to("/admin/users"))
+    }
+}
+
+// api tokens of the current editor
+pub mod tokens {
+    use crate::auth::{Editor, TokenForm};
+    use crate::models::token::ApiToken;
+    use crate::{Db, DreamResult};
+    use rocket::request::Form;
+    use rocket::response::Redirect;
+    use rocket_contrib::templates::Template;
+
+    // tokens of the editor, a new one is shown only right after creation
+    #[derive(Serialize)]
+    struct TokensContext {
+        items: Vec<ApiToken>,
+        created: Option<String>,
+    }
+
+    #[get("/")]
+    pub fn list(editor: Editor, connection: Db) -> DreamResult<Template> {
+        let items = ApiToken::all(&connection, editor.0)?;
+
+        Ok(Template::render(
+            "admin/tokens/list",
+            TokensContext {
+                items,
+                created: None,
+            },
+        ))
+    }
+
+    #[post("/", data = "<token_form>")]
+    pub fn create(
+        editor: Editor,
+        connection: Db,
+        token_form: Form<TokenForm>,
+    ) -> DreamResult<Template> {
+        let (_api_token, token) = ApiToken::insert(&connection, editor.0, token_form.name.trim())?;
+        let items = ApiToken::all(&connection, editor.0)?;
+
+        Ok(Template::render(
+            "admin/tokens/list",
+            TokensContext {
+                items,
+                created: Some(token),
+            },
+        ))
+    }
+
+    #[delete("/<id>")]
+    pub fn delete(editor: Editor, connection: Db, id: i32) -> DreamResult<Redirect> {
+        let _token = ApiToken::delete(&connection, editor.0, id)?;
+
+        Ok(Redirect::to("/admin/tokens"))
+    }
+}
+
+// moving content between instances, e.g. staging -> production
+pub mod bundle {
+    use crate::auth::Editor;
+    use crate::bundle::{export as export_bundle, import as import_bundle, Conflict, ImportReport};
+    use crate::errors::DreamError;
+    use crate::timezone::Timezone;
+    use crate::{Db, DreamResult};
+    use rocket::http::ContentType;
+    use rocket::response::Response;
+    use rocket::Data;
+    use rocket_contrib::templates::Template;
+    use rocket_multipart_form_data::{
+        FileField, MultipartFormData, MultipartFormDataField, MultipartFormDataOptions, TextField,
+    };
+    use std::io::Cursor;
+
+    // images included, so much bigger than a single upload
+    const BUNDLE_SIZE_LIMIT: u64 = 64 * 1024 * 1024;
+
+    #[derive(Serialize)]
+    pub struct BundleContext {
+        report: Option<ImportReport>,
+    }
+
+    #[get("/")]
+    pub fn page(_editor: Editor) -> Template {
+        Template::render("admin/bundle", BundleContext { report: None })
+    }
+
+    // zip with glyphs, tones, kins and their images
+    #[get("/export")]
+    pub fn export(
+        _editor: Editor,
+        connection: Db,
+        timezone: Timezone,
+    ) -> DreamResult<Response<'static>> {
+        let bytes = export_bundle(&connection)?;
+        let today = timezone.today().format("%Y_%m_%d");
+
+        Ok(Response::build()
+            .header(ContentType::new("application", "zip"))
+            .raw_header(
+                "Content-Disposition",
+                format!("attachment; filename=\"dreamspell_{}.zip\"", today),
+            )
+            .sized_body(Cursor::new(bytes))
+            .finalize())
+    }
+
+    #[post("/import", data = "<data>")]
+    pub fn import(
+        _editor: Editor,
+        connection: Db,
+        content_type: &ContentType,
+        data: Data,
+    ) -> DreamResult<Template> {
+        let mut options = MultipartFormDataOptions::new();
+        options
+            .allowed_fields
+            .push(MultipartFormDataField::file("bundle").size_limit(BUNDLE_SIZE_LIMIT));
+        options
+            .allowed_fields
+            .push(MultipartFormDataField::text("conflict"));
+
+        let form = MultipartFormData::parse(content_type, data, options).map_err(|error| {
+            println!("Multipart form parsing error: {:?}", error);
+            DreamError::BadRequest
+        })?;
+        let path = match form.files.get("bundle") {
+            Some(FileField::Single(file)) => &file.path,
+            _ => return Err(DreamError::BadRequest),
+        };
+        let conflict = match form.texts.get("conflict") {
+            Some(TextField::Single(text)) if text.text == "overwrite" => Conflict::Overwrite,
+            _ => Conflict::Skip,
+        };
+
+        let bytes = std::fs::read(path).map_err(|error| {
+            println!("File error: {}", error);
+            DreamError::InternalServerError
+        })?;
+        let report = import_bundle(&connection, &bytes, conflict)?;
+
+        Ok(Template::render(
+            "admin/bundle",
+            BundleContext {
+                report: Some(report),
+            },
+        ))
+    }
+}
@@ -0,0 +1,334 @@
+use crate::errors::DreamError;
+use crate::models::form::{image_name_error, MultipartModel};
+use crate::models::reading::DayReading;
+use crate::timezone::Timezone;
+use crate::views::forms::FormDate;
+use crate::{Db, DreamResult};
+use chrono::NaiveDate;
+use rocket::http::Status;
+use rocket::response::status::Custom;
+use rocket_contrib::json::Json;
+
+#[get("/today")]
+pub fn today(connection: Db, timezone: Timezone) -> DreamResult<Json<DayReading>> {
+    Ok(Json(DayReading::load(&connection, timezone.today())?))
+}
+
+// full reading of a date, missing or broken date is a bad request
+#[get("/calc?<date>")]
+pub fn calc(
+    connection: Db,
+    date: Option<FormDate>,
+) -> DreamResult<Result<Json<DayReading>, Custom<Json<ApiError>>>> {
+    match required_date(date) {
+        Ok(date) => Ok(Ok(Json(DayReading::load(&connection, date)?))),
+        Err(error) => Ok(Err(error)),
+    }
+}
+
+pub fn required_date(date: Option<FormDate>) -> Result<NaiveDate, Custom<Json<ApiError>>> {
+    date.map(|date| date.0)
+        .ok_or_else(|| bad_request("date must be yyyy-mm-dd"))
+}
+
+// 400 with the reason in json
+pub fn bad_request(error: &'static str) -> Custom<Json<ApiError>> {
+    Custom(Status::BadRequest, Json(ApiError::new(error)))
+}
+
+#[derive(Serialize, Debug)]
+pub struct ApiError {
+    error: &'static str,
+}
+
+impl ApiError {
+    pub fn new(error: &'static str) -> ApiError {
+        ApiError { error }
+    }
+}
+
+pub const PER_PAGE: i64 = 20;
+pub const MAX_PER_PAGE: i64 = 100;
+
+// content item with a link to its image
+#[derive(Serialize)]
+pub struct ApiItem<T> {
+    #[serde(flatten)]
+    item: T,
+    image_url: Option<String>,
+}
+
+impl<T> ApiItem<T> {
+    pub fn new(item: T, image: &str) -> ApiItem<T> {
+        ApiItem {
+            item,
+            image_url: crate::models::utils::file_url(image),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct ApiPage<T> {
+    items: Vec<ApiItem<T>>,
+    page: i64,
+    per_page: i64,
+    total: i64,
+    pages: i64,
+}
+
+// pages start from 1, broken paging is a bad request
+pub fn paging(page: Option<i64>, per_page: Option<i64>) -> DreamResult<(i64, i64)> {
+    let page = page.unwrap_or(1);
+    let per_page = per_page.unwrap_or(PER_PAGE);
+    if page < 1 || per_page < 1 || per_page > MAX_PER_PAGE {
+        return Err(DreamError::BadRequest);
+    }
+    // the offset of the page has to fit into i64
+    if (page - 1).checked_mul(per_page).is_none() {
+        return Err(DreamError::BadRequest);
+    }
+
+    Ok((page, per_page))
+}
+
+// model checks plus the image, which is only referenced by name here
+pub fn validate<T: MultipartModel>(item: &T, image: &str) -> DreamResult<()> {
+    let mut errors = item.validate();
+    if let Some(message) = image_name_error(image) {
+        errors.insert("image", message);
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(DreamError::Invalid(errors))
+    }
+}
+
+macro_rules! resource {
+    ($t:ty, $nt:ty, $tp:expr) => {
+        // paginated list ordered by num
+        #[get("/?<page>&<per_page>")]
+        pub fn list(
+            connection: crate::Db,
+            page: Option<i64>,
+            per_page: Option<i64>,
+        ) -> crate::DreamResult<rocket_contrib::json::Json<crate::views::api::ApiPage<$t>>> {
+            let (page, per_page) = crate::views::api::paging(page, per_page)?;
+            let total = <$t>::count(&connection)?;
+            let items = <$t>::page(&connection, page - 1, per_page)?
+                .into_iter()
+                .map(|item| {
+                    let image = item.image.clone();
+                    crate::views::api::ApiItem::new(item, &image)
+                })
+                .collect();
+
+            Ok(rocket_contrib::json::Json(crate::views::api::ApiPage {
+                items,
+                page,
+                per_page,
+                total,
+                pages: (total + per_page - 1) / per_page,
+            }))
+        }
+
+        // single item by id
+        #[get("/<id>")]
+        pub fn get(
+            connection: crate::Db,
+            id: i32,
+        ) -> crate::DreamResult<rocket_contrib::json::Json<crate::views::api::ApiItem<$t>>> {
+            let item = <$t>::get(&connection, id)?;
+            let image = item.image.clone();
+
+            Ok(rocket_contrib::json::Json(crate::views::api::ApiItem::new(
+                item, &image,
+            )))
+        }
+
+        // create item, editors only
+        #[post("/", format = "json", data = "<new_item>")]
+        pub fn create(
+            _editor: crate::auth::ApiEditor,
+            connection: crate::Db,
+            new_item: rocket_contrib::json::Json<$nt>,
+        ) -> crate::DreamResult<
+            rocket::response::status::Created<
+                rocket_contrib::json::Json<crate::views::api::ApiItem<$t>>,
+            >,
+        > {
+            let new_item = new_item.into_inner();
+            crate::views::api::validate(&new_item, &new_item.image)?;
+
+            let item = <$t>::insert(&connection, new_item)?;
+            let image = item.image.clone();
+
+            Ok(rocket::response::status::Created(
+                format!("/{}/{}", $tp, item.id),
+                Some(rocket_contrib::json::Json(crate::views::api::ApiItem::new(
+                    item, &image,
+                ))),
+            ))
+        }
+
+        // replace item, empty image keeps the old one
+        #[put("/<id>", format = "json", data = "<new_item>")]
+        pub fn update(
+            _editor: crate::auth::ApiEditor,
+            connection: crate::Db,
+            id: i32,
+            new_item: rocket_contrib::json::Json<$nt>,
+        ) -> crate::DreamResult<rocket_contrib::json::Json<crate::views::api::ApiItem<$t>>> {
+            let new_item = new_item.into_inner();
+            crate::views::api::validate(&new_item, &new_item.image)?;
+
+            let item = <$t>::update(&connection, new_item, id)?;
+            let image = item.image.clone();
+
+            Ok(rocket_contrib::json::Json(crate::views::api::ApiItem::new(
+                item, &image,
+            )))
+        }
+
+        // delete item with its image
+        #[delete("/<id>")]
+        pub fn delete(
+            _editor: crate::auth::ApiEditor,
+            connection: crate::Db,
+            id: i32,
+        ) -> crate::DreamResult<rocket_contrib::json::Json<crate::views::api::ApiItem<$t>>> {
+            let item = <$t>::delete(&connection, id)?;
+            let image = item.image.clone();
+
+            Ok(rocket_contrib::json::Json(crate::views::api::ApiItem::new(
+                item, &image,
+            )))
+        }
+    };
+}
+
+pub mod glyphs {
+    use crate::models::content::Content;
+    use crate::models::glyph::{Glyph, NewGlyph};
+    resource!(Glyph, NewGlyph, "api/v1/glyphs");
+}
+
+pub mod tones {
+    use crate::models::content::Content;
+    use crate::models::tone::{NewTone, Tone};
+    resource!(Tone, NewTone, "api/v1/tones");
+}
+
+pub mod kins {
+    use crate::models::content::Content;
+    use crate::models::kin::{Kin, NewKin};
+    resource!(Kin, NewKin, "api/v1/kins");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::glyph::NewGlyph;
+    use rocket::local::Client;
+
+    #[get("/broken")]
+    fn broken() -> DreamResult<()> {
+        Err(DreamError::BadRequest)
+    }
+
+    // calc without the database
+    #[get("/date?<date>")]
+    fn date(date: Option<FormDate>) -> Result<String, Custom<Json<ApiError>>> {
+        required_date(date).map(|date| date.to_string())
+    }
+
+    fn client() -> Client {
+        let rocket = rocket::ignite()
+            .mount("/api", routes![broken, date])
+            .register(catchers![crate::views::pages::bad_request]);
+
+        Client::new(rocket).unwrap()
+    }
+
+    #[test]
+    fn json_bad_requests() {
+        let client = client();
+
+        let mut response = client.get("/api/broken").dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+        assert_eq!(
+            response.body_string(),
+            Some(r#"{"error":"bad request"}"#.to_string())
+        );
+
+        let mut response = client.get("/api/date?date=2020-03-01").dispatch();
+        assert_eq!(response.body_string(), Some("2020-03-01".to_string()));
+
+        for uri in &[
+            "/api/date",
+            "/api/date?date=2020-13-01",
+            "/api/date?date=tomorrow",
+        ] {
+            let mut response = client.get(*uri).dispatch();
+            assert_eq!(response.status(), Status::BadRequest);
+            assert_eq!(
+                response.body_string(),
+                Some(r#"{"error":"date must be yyyy-mm-dd"}"#.to_string())
+            );
+        }
+    }
+
+    fn bad_paging(page: Option<i64>, per_page: Option<i64>) -> bool {
+        match paging(page, per_page) {
+            Err(DreamError::BadRequest) => true,
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn pages() {
+        assert_eq!(paging(None, None).unwrap(), (1, PER_PAGE));
+        assert_eq!(paging(Some(3), Some(50)).unwrap(), (3, 50));
+        assert_eq!(
+            paging(Some(1), Some(MAX_PER_PAGE)).unwrap(),
+            (1, MAX_PER_PAGE)
+        );
+
+        assert!(bad_paging(Some(0), None));
+        assert!(bad_paging(Some(-1), None));
+        assert!(bad_paging(None, Some(0)));
+        assert!(bad_paging(None, Some(-5)));
+        assert!(bad_paging(None, Some(MAX_PER_PAGE + 1)));
+        assert!(bad_paging(Some(i64::MAX), None));
+        assert!(bad_paging(Some(i64::MAX / 10), Some(MAX_PER_PAGE)));
+        assert_eq!(paging(Some(i64::MAX), Some(1)).unwrap(), (i64::MAX, 1));
+    }
+
+    fn glyph(image: &str) -> NewGlyph {
+        NewGlyph {
+            num: 3,
+            name: "Синяя Ночь".to_string(),
+            image: image.to_string(),
+            ..NewGlyph::default()
+        }
+    }
+
+    fn image_error(image: &str) -> Option<&'static str> {
+        match validate(&glyph(image), image) {
+            Err(DreamError::Invalid(errors)) => errors.get("image").cloned(),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn rejects_image_paths() {
+        assert!(validate(&glyph(""), "").is_ok());
+        assert_eq!(
+            image_error("../../victim.txt"),
+            Some("Недопустимое имя файла")
+        );
+        assert_eq!(image_error("/etc/passwd"), Some("Недопустимое имя файла"));
+        assert_eq!(image_error("missing.png"), Some("Изображение не найдено"));
+    }
+}
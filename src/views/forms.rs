@@ -0,0 +1,85 @@
+use crate::models::profile::NewProfile;
+use chrono::{Datelike, NaiveDate};
+use rocket::http::RawStr;
+use rocket::request::FromFormValue;
+
+// date from html date input: yyyy-mm-dd, years 1-9999 only
+#[derive(Debug, Clone, Copy)]
+pub struct FormDate(pub NaiveDate);
+
+impl<'v> FromFormValue<'v> for FormDate {
+    type Error = &'v RawStr;
+
+    fn from_form_value(form_value: &'v RawStr) -> Result<FormDate, &'v RawStr> {
+        match NaiveDate::parse_from_str(form_value.as_str(), "%Y-%m-%d") {
+            Ok(date) if date.year() >= 1 && date.year() <= 9999 => Ok(FormDate(date)),
+            _ => Err(form_value),
+        }
+    }
+}
+
+// required text field, trimmed, blank is rejected like a missing date
+#[derive(Debug, Clone)]
+pub struct FormName(pub String);
+
+impl<'v> FromFormValue<'v> for FormName {
+    type Error = &'v RawStr;
+
+    fn from_form_value(form_value: &'v RawStr) -> Result<FormName, &'v RawStr> {
+        match form_value.url_decode() {
+            Ok(name) if !name.trim().is_empty() => Ok(FormName(name.trim().to_string())),
+            _ => Err(form_value),
+        }
+    }
+}
+
+#[derive(FromForm)]
+pub struct CalcForm {
+    pub date: Option<FormDate>,
+    // 13-moon year for the year bearer, current by default
+    pub year: Option<i32>,
+}
+
+// utc offset like +06:00, empty to use the server default
+#[derive(FromForm)]
+pub struct TimezoneForm {
+    pub tz: String,
+}
+
+#[derive(FromForm)]
+pub struct ProfileForm {
+    pub name: FormName,
+    pub birthday: FormDate,
+    pub notes: String,
+}
+
+impl ProfileForm {
+    pub fn into_new_profile(self, user_id: i32) -> NewProfile {
+        NewProfile {
+            user_id,
+            name: self.name.0,
+            birthday: self.birthday.0,
+            notes: self.notes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::request::{FormItems, FromForm};
+
+    fn profile_form(query: &str) -> Option<ProfileForm> {
+        ProfileForm::from_form(&mut FormItems::from(query), true).ok()
+    }
+
+    #[test]
+    fn profile_names() {
+        let form = profile_form("name=+%D0%90%D0%BD%D1%8F+&birthday=1990-05-17&notes=").unwrap();
+        assert_eq!(form.name.0, "Аня");
+        assert!(profile_form("name=&birthday=1990-05-17&notes=").is_none());
+        assert!(profile_form("name=+++&birthday=1990-05-17&notes=").is_none());
+        assert!(profile_form("birthday=1990-05-17&notes=").is_none());
+        assert!(profile_form("name=Anna&birthday=&notes=").is_none());
+    }
+}
@@ -1,5 +1,10 @@
+use crate::models::form::FieldErrors;
+
 pub mod admin;
+pub mod api;
+pub mod forms;
 pub mod pages;
+pub mod profiles;
 
 #[derive(Serialize)]
 pub struct NoContext {}
@@ -8,3 +13,23 @@ pub struct NoContext {}
 pub struct TemplateContext<T> {
     items: Vec<T>,
 }
+
+// add and edit forms: values, field errors and the current image
+#[derive(Serialize)]
+pub struct FormContext<T> {
+    id: Option<i32>,
+    image: String,
+    item: T,
+    errors: FieldErrors,
+}
+
+impl<T> FormContext<T> {
+    pub fn new(id: Option<i32>, image: String, item: T, errors: FieldErrors) -> FormContext<T> {
+        FormContext {
+            id,
+            image,
+            item,
+            errors,
+        }
+    }
+}
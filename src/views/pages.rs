@@ -1,34 +1,278 @@
-use crate::auth::LoginForm;
+use crate::auth::{LoginForm, RegistrationForm, LOGIN_TAKEN};
+use crate::calendar::{
+    moon::{year_of, MoonDate},
+    oracle::Oracle,
+    relationship::Relationship,
+    signature::Signature,
+    wavespell::Wavespell,
+};
+use crate::errors::DreamError;
+use crate::models::reading::{
+    DayReading, OracleReading, Reading, RelationshipReading, Tzolkin, WavespellReading, YearReading,
+};
+use crate::models::user::{Role, User};
+use crate::timezone::{parse_offset, Timezone};
+use crate::views::api::ApiError;
+use crate::views::forms::{CalcForm, FormDate, TimezoneForm};
 use crate::{views::NoContext, Db, DreamResult};
+use diesel::OptionalExtension;
 use rocket::http::{Cookie, Cookies};
-use rocket::request::Form;
+use rocket::request::{Form, Request};
 use rocket::response::Redirect;
+use rocket_contrib::json::Json;
 use rocket_contrib::templates::Template;
 
+#[derive(Serialize)]
+pub struct CalcContext {
+    date: String,
+    moon_date: Option<MoonDate>,
+    reading: Option<Reading>,
+    oracle: Option<OracleReading>,
+    year: Option<YearReading>,
+    invalid: bool,
+    invalid_year: bool,
+}
+
+#[derive(Serialize)]
+pub struct TodayContext {
+    #[serde(flatten)]
+    day: DayReading,
+    timezone: String,
+}
+
+#[derive(Serialize)]
+pub struct MoonContext {
+    date: String,
+    moon_date: Option<MoonDate>,
+}
+
+#[derive(Serialize)]
+pub struct RegistrationContext {
+    login: String,
+    errors: Vec<&'static str>,
+}
+
+#[derive(Serialize)]
+pub struct KinContext {
+    reading: Reading,
+    oracle: OracleReading,
+    wavespell: WavespellReading,
+}
+
+#[derive(Serialize)]
+pub struct WavespellContext {
+    wavespell: WavespellReading,
+}
+
+#[derive(Serialize)]
+pub struct RelationshipContext {
+    first_date: String,
+    second_date: String,
+    comparison: Option<RelationshipReading>,
+    // one of the dates is 0.0 hunab ku
+    hunab_ku: bool,
+}
+
+#[derive(Serialize)]
+pub struct OracleContext {
+    reading: Reading,
+    oracle: OracleReading,
+}
+
 #[get("/")]
-pub fn index(_connection: Db) -> Template {
+pub fn index() -> Template {
     Template::render("pages/index", NoContext {})
 }
 
+#[post("/", data = "<calc_form>")]
+pub fn calculate(
+    connection: Db,
+    timezone: Timezone,
+    calc_form: Form<CalcForm>,
+) -> DreamResult<Template> {
+    let mut context = CalcContext {
+        date: "".to_string(),
+        moon_date: None,
+        reading: None,
+        oracle: None,
+        year: None,
+        invalid: calc_form.date.is_none(),
+        invalid_year: false,
+    };
+
+    if let Some(date) = calc_form.date {
+        context.date = date.0.format("%Y-%m-%d").to_string();
+        context.moon_date = Some(MoonDate::from_date(date.0));
+        let year = calc_form.year.unwrap_or_else(|| year_of(timezone.today()));
+        context.year = YearReading::load(&connection, date.0, year)?;
+        context.invalid_year = context.year.is_none();
+        // nothing to show for 0.0 hunab ku
+        if let Some(signature) = Signature::from_date(date.0) {
+            context.reading = Some(Reading::load(&connection, signature)?);
+            context.oracle = Some(OracleReading::load(&connection, Oracle::new(signature))?);
+        }
+    }
+
+    Ok(Template::render("pages/index", context))
+}
+
+#[get("/oracle/<kin>")]
+pub fn oracle(connection: Db, kin: i32) -> DreamResult<Template> {
+    let signature = Signature::new(kin).ok_or(DreamError::NotFound)?;
+    let context = OracleContext {
+        reading: Reading::load(&connection, signature)?,
+        oracle: OracleReading::load(&connection, Oracle::new(signature))?,
+    };
+
+    Ok(Template::render("pages/oracle", context))
+}
+
+// kin of the day in the reader's timezone
+#[get("/today")]
+pub fn today(connection: Db, timezone: Timezone) -> DreamResult<Template> {
+    let context = TodayContext {
+        day: DayReading::load(&connection, timezone.today())?,
+        timezone: timezone.name(),
+    };
+
+    Ok(Template::render("pages/today", context))
+}
+
+// remember the reader's timezone, an empty or broken value resets it
+#[post("/timezone", data = "<timezone_form>")]
+pub fn set_timezone(mut cookies: Cookies, timezone_form: Form<TimezoneForm>) -> Redirect {
+    match parse_offset(&timezone_form.tz) {
+        Some(offset) => cookies.add(Cookie::build("tz", offset.to_string()).path("/").finish()),
+        None => cookies.remove(Cookie::build("tz", "").path("/").finish()),
+    }
+
+    Redirect::to("/today")
+}
+
+#[get("/kin/<num>")]
+pub fn kin(connection: Db, num: i32) -> DreamResult<Template> {
+    let signature = Signature::new(num).ok_or(DreamError::NotFound)?;
+    let context = KinContext {
+        reading: Reading::load(&connection, signature)?,
+        oracle: OracleReading::load(&connection, Oracle::new(signature))?,
+        wavespell: WavespellReading::load(&connection, Wavespell::new(signature))?,
+    };
+
+    Ok(Template::render("pages/kin", context))
+}
+
+#[get("/wavespell/<kin>")]
+pub fn wavespell(connection: Db, kin: i32) -> DreamResult<Template> {
+    let signature = Signature::new(kin).ok_or(DreamError::NotFound)?;
+    let context = WavespellContext {
+        wavespell: WavespellReading::load(&connection, Wavespell::new(signature))?,
+    };
+
+    Ok(Template::render("pages/wavespell", context))
+}
+
+#[get("/wavespell?<date>")]
+pub fn wavespell_by_date(date: FormDate) -> DreamResult<Redirect> {
+    let signature = Signature::from_date(date.0).ok_or(DreamError::NotFound)?;
+
+    Ok(Redirect::to(format!("/wavespell/{}", signature.kin)))
+}
+
+// harmonic module with the kin of the date highlighted
+#[get("/tzolkin?<date>")]
+pub fn tzolkin(connection: Db, date: Option<FormDate>) -> DreamResult<Template> {
+    let selected = date.and_then(|date| Signature::from_date(date.0));
+    let context = Tzolkin::load(&connection, selected)?;
+
+    Ok(Template::render("pages/tzolkin", context))
+}
+
+// compatibility of two birth dates
+#[get("/relationship?<first>&<second>")]
+pub fn relationship(
+    connection: Db,
+    first: Option<FormDate>,
+    second: Option<FormDate>,
+) -> DreamResult<Template> {
+    let mut context = RelationshipContext {
+        first_date: format_date(first),
+        second_date: format_date(second),
+        comparison: None,
+        hunab_ku: false,
+    };
+
+    if let (Some(first), Some(second)) = (first, second) {
+        match (
+            Signature::from_date(first.0),
+            Signature::from_date(second.0),
+        ) {
+            (Some(first), Some(second)) => {
+                let relationship = Relationship::new(first, second);
+                context.comparison = Some(RelationshipReading::load(&connection, relationship)?);
+            }
+            _ => context.hunab_ku = true,
+        }
+    }
+
+    Ok(Template::render("pages/relationship", context))
+}
+
+fn format_date(date: Option<FormDate>) -> String {
+    date.map(|date| date.0.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+// convert gregorian date to 13-moon calendar or back
+#[get("/moon?<date>&<year>&<moon>&<day>")]
+pub fn moon(
+    date: Option<FormDate>,
+    year: Option<i32>,
+    moon: Option<i32>,
+    day: Option<i32>,
+) -> Template {
+    let moon_date = match (date, year, moon, day) {
+        (Some(date), _, _, _) => Some(MoonDate::from_date(date.0)),
+        (None, Some(year), Some(moon), Some(day)) => MoonDate::day(year, moon, day),
+        _ => None,
+    };
+    let context = MoonContext {
+        date: moon_date
+            .and_then(|moon_date| moon_date.to_date())
+            .map(|date| date.format("%Y-%m-%d").to_string())
+            .unwrap_or_default(),
+        moon_date,
+    };
+
+    Template::render("pages/moon", context)
+}
+
 #[get("/login")]
 pub fn login_page() -> Template {
     Template::render("login", NoContext {})
 }
 
 #[post("/login", data = "<login_form>")]
-pub fn login(mut cookies: Cookies, login_form: Form<LoginForm>) -> DreamResult<Redirect> {
-    if login_form.password == "42" {
-        cookies.add_private(Cookie::new("admin", 1.to_string()));
+pub fn login(
+    connection: Db,
+    mut cookies: Cookies,
+    login_form: Form<LoginForm>,
+) -> DreamResult<Redirect> {
+    match User::login(&connection, &login_form.login, &login_form.password)? {
+        Some(user) => {
+            cookies.add_private(Cookie::new("user", user.id.to_string()));
 
-        Ok(Redirect::to("/admin"))
-    } else {
-        Ok(Redirect::to("/login"))
+            match user.role() {
+                Role::Member => Ok(Redirect::to("/")),
+                _ => Ok(Redirect::to("/admin")),
+            }
+        }
+        None => Ok(Redirect::to("/login")),
     }
 }
 
 #[get("/logout")]
 pub fn logout(mut cookies: Cookies) -> Redirect {
-    cookies.remove_private(Cookie::named("admin"));
+    cookies.remove_private(Cookie::named("user"));
 
     Redirect::to("/")
 }
@@ -38,12 +282,84 @@ pub fn registration_page() -> Template {
     Template::render("registration", NoContext {})
 }
 
+// errors are rendered back into the form
+#[post("/registration", data = "<registration_form>")]
+pub fn registration(
+    connection: Db,
+    mut cookies: Cookies,
+    registration_form: Form<RegistrationForm>,
+) -> DreamResult<Result<Redirect, Template>> {
+    let login = registration_form.login.trim();
+    let mut errors = registration_form.validate();
+    if errors.is_empty() && User::by_name(&connection, login).optional()?.is_some() {
+        errors.push(LOGIN_TAKEN);
+    }
+
+    if errors.is_empty() {
+        match User::insert(&connection, login, &registration_form.password) {
+            Ok(user) => {
+                cookies.add_private(Cookie::new("user", user.id.to_string()));
+
+                return Ok(Ok(Redirect::to("/")));
+            }
+            // the same name registered right after the check
+            Err(DreamError::Invalid(field_errors)) => errors.extend(field_errors.values()),
+            Err(error) => return Err(error),
+        }
+    }
+
+    let context = RegistrationContext {
+        login: login.to_string(),
+        errors,
+    };
+
+    Ok(Err(Template::render("registration", context)))
+}
+
 #[catch(404)]
 pub fn not_found() -> Template {
     Template::render("404", NoContext {})
 }
 
+// api clients get json instead of the login page
 #[catch(401)]
-pub fn unauthorized() -> Redirect {
-    Redirect::to("/login")
+pub fn unauthorized(request: &Request) -> Result<Redirect, Json<ApiError>> {
+    if is_api(request) {
+        Err(Json(ApiError::new("unauthorized")))
+    } else {
+        Ok(Redirect::to("/login"))
+    }
+}
+
+#[catch(403)]
+pub fn forbidden(request: &Request) -> Result<Template, Json<ApiError>> {
+    if is_api(request) {
+        Err(Json(ApiError::new("forbidden")))
+    } else {
+        Ok(Template::render("403", NoContext {}))
+    }
+}
+
+// broken query or body, e.g. paging out of range or malformed json
+#[catch(400)]
+pub fn bad_request(request: &Request) -> Result<Template, Json<ApiError>> {
+    if is_api(request) {
+        Err(Json(ApiError::new("bad request")))
+    } else {
+        Ok(Template::render("400", NoContext {}))
+    }
+}
+
+// json that doesn't fit the model
+#[catch(422)]
+pub fn unprocessable(request: &Request) -> Result<Template, Json<ApiError>> {
+    if is_api(request) {
+        Err(Json(ApiError::new("unprocessable entity")))
+    } else {
+        Ok(Template::render("400", NoContext {}))
+    }
+}
+
+fn is_api(request: &Request) -> bool {
+    request.uri().path().starts_with("/api/")
 }
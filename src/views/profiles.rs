@@ -0,0 +1,64 @@
+use crate::auth::Member;
+use crate::models::profile::Profile;
+use crate::models::reading::ProfileReading;
+use crate::views::forms::ProfileForm;
+use crate::views::{NoContext, TemplateContext};
+use crate::{Db, DreamResult};
+use rocket::request::Form;
+use rocket::response::Redirect;
+use rocket_contrib::templates::Template;
+
+// profiles of the logged in user with their readings
+#[get("/")]
+pub fn list(member: Member, connection: Db) -> DreamResult<Template> {
+    let items = Profile::all(&connection, member.0)?
+        .into_iter()
+        .map(|profile| ProfileReading::load(&connection, profile))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Template::render("profiles/list", TemplateContext { items }))
+}
+
+#[get("/add")]
+pub fn add(_member: Member) -> Template {
+    Template::render("profiles/add", NoContext {})
+}
+
+#[post("/", data = "<profile_form>")]
+pub fn create(
+    member: Member,
+    connection: Db,
+    profile_form: Form<ProfileForm>,
+) -> DreamResult<Redirect> {
+    let new_profile = profile_form.into_inner().into_new_profile(member.0);
+    let _profile = Profile::insert(&connection, new_profile)?;
+
+    Ok(Redirect::to("/profiles"))
+}
+
+#[get("/<id>")]
+pub fn edit(member: Member, connection: Db, id: i32) -> DreamResult<Template> {
+    let profile = Profile::get(&connection, member.0, id)?;
+
+    Ok(Template::render("profiles/edit", profile))
+}
+
+#[put("/<id>", data = "<profile_form>")]
+pub fn update(
+    member: Member,
+    connection: Db,
+    id: i32,
+    profile_form: Form<ProfileForm>,
+) -> DreamResult<Redirect> {
+    let new_profile = profile_form.into_inner().into_new_profile(member.0);
+    let _profile = Profile::update(&connection, new_profile, id)?;
+
+    Ok(Redirect::to("/profiles"))
+}
+
+#[delete("/<id>")]
+pub fn delete(member: Member, connection: Db, id: i32) -> DreamResult<Redirect> {
+    let _profile = Profile::delete(&connection, member.0, id)?;
+
+    Ok(Redirect::to("/profiles"))
+}